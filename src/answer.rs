@@ -0,0 +1,67 @@
+use crate::SolveResult;
+use std::{collections::HashMap, io};
+
+impl SolveResult {
+    /// 内部の block id (共有 1.., 片側 10000.., 空 0, 禁止 `!0`) を出力用の 1..n に振り直す
+    pub fn block_id_map(&self) -> HashMap<u16, usize> {
+        let mut block_id_map = HashMap::new();
+        for &g in self.grids.iter().flatten() {
+            if g == 0 || g == !0 {
+                continue;
+            }
+            let id = block_id_map.len() + 1;
+            block_id_map.entry(g).or_insert(id);
+        }
+        block_id_map
+    }
+
+    /// 解がまだないとき (`worst`) は 0
+    pub(crate) fn d(&self) -> usize {
+        let len = self.grids.first().map_or(0, |g| g.len());
+        let mut d = 0;
+        while d * d * d < len {
+            d += 1;
+        }
+        d
+    }
+
+    /// The answer as a `tools::Output`; empty (no objects, no blocks) for `SolveResult::worst`.
+    pub fn to_output(&self) -> tools::Output {
+        let block_id_map = self.block_id_map();
+        let d = self.d();
+        let mut b = vec![vec![vec![vec![0; d]; d]; d]; self.grids.len()];
+        for (i, g) in self.grids.iter().enumerate() {
+            for (j, c) in g.iter().enumerate() {
+                b[i][j / (d * d)][j / d % d][j % d] = block_id_map.get(c).copied().unwrap_or(0);
+            }
+        }
+        tools::Output {
+            n: block_id_map.len(),
+            b,
+        }
+    }
+
+    /// Writes the answer in the contest output format.
+    pub fn write_ans<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        fn write_v<W: io::Write>(
+            w: &mut W,
+            v: &[u16],
+            block_id_map: &HashMap<u16, usize>,
+        ) -> io::Result<()> {
+            for (i, g) in v.iter().enumerate() {
+                if i != 0 {
+                    write!(w, " ")?;
+                }
+                write!(w, "{}", block_id_map.get(g).unwrap_or(&0))?;
+            }
+            writeln!(w)
+        }
+
+        let block_id_map = self.block_id_map();
+        writeln!(w, "{}", block_id_map.len())?;
+        for g in self.grids.iter() {
+            write_v(w, g, &block_id_map)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{McParams, MoveSet};
+use serde::{Deserialize, Serialize};
+
+/// `erase_small_th` と `erase_shared_p` を表の値の何倍で試すか
+const SCALES: [f64; 3] = [0.5, 1.0, 2.0];
+/// 1 ステップごとに過去の記録をこれだけ割り引く (おおよそ直近 1000 ステップを見る)
+const DISCOUNT: f64 = 0.999;
+/// 改善率は高々数 % なので、探索の項もそれに合わせて小さくする
+const EXPLORATION: f64 = 0.01;
+
+/// One arm of `Selection::Bandit`: a move with the erase parameters of its steps.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArmStats {
+    /// Index in the `MoveSet`.
+    pub index: usize,
+    pub name: String,
+    pub erase_small_th: usize,
+    pub erase_shared_p: f64,
+    pub tried: u64,
+    pub accepted: u64,
+    pub improved: u64,
+    /// 割り引いた試行回数
+    pub recent_tried: f64,
+    /// 割り引いた改善回数
+    pub recent_improved: f64,
+}
+
+impl ArmStats {
+    /// Improvement rate over the recent steps.
+    pub fn rate(&self) -> f64 {
+        if self.recent_tried > 0.0 {
+            self.recent_improved / self.recent_tried
+        } else {
+            0.0
+        }
+    }
+
+    /// `params` with the erase parameters of this arm.
+    pub fn params(&self, params: &McParams) -> McParams {
+        McParams {
+            erase_small_th: self.erase_small_th,
+            erase_shared_p: self.erase_shared_p,
+            ..*params
+        }
+    }
+}
+
+/// Every move of `moves` with a positive weight, with `erase_small_th` and `erase_shared_p`
+/// of `params` scaled by each of `SCALES`.
+pub fn bandit_arms(moves: &MoveSet, params: &McParams) -> Vec<ArmStats> {
+    let mut arms: Vec<ArmStats> = vec![];
+    for index in (0..moves.len()).filter(|&i| moves.weight(i) > 0.0) {
+        for &th in SCALES.iter() {
+            for &p in SCALES.iter() {
+                let erase_small_th = (params.erase_small_th as f64 * th).round() as usize;
+                let erase_shared_p = (params.erase_shared_p * p).min(1.0);
+                // th = 0 などで同じ腕になるものはまとめる
+                if arms.iter().any(|a| {
+                    a.index == index
+                        && a.erase_small_th == erase_small_th
+                        && a.erase_shared_p == erase_shared_p
+                }) {
+                    continue;
+                }
+                arms.push(ArmStats {
+                    index,
+                    name: moves.name(index).to_owned(),
+                    erase_small_th,
+                    erase_shared_p,
+                    tried: 0,
+                    accepted: 0,
+                    improved: 0,
+                    recent_tried: 0.0,
+                    recent_improved: 0.0,
+                });
+            }
+        }
+    }
+    arms
+}
+
+/// The arm with the best discounted UCB score; arms never tried come first.
+pub fn choose_arm(arms: &[ArmStats]) -> usize {
+    if let Some(i) = arms.iter().position(|a| a.tried == 0) {
+        return i;
+    }
+    let total: f64 = arms.iter().map(|a| a.recent_tried).sum();
+    let ucb = |a: &ArmStats| a.rate() + EXPLORATION * (total.ln().max(0.0) / a.recent_tried).sqrt();
+    let mut best = 0;
+    for i in 1..arms.len() {
+        if ucb(&arms[i]) > ucb(&arms[best]) {
+            best = i;
+        }
+    }
+    best
+}
+
+/// Records the outcome of a step made with arm `chosen`.
+pub fn update_arms(arms: &mut [ArmStats], chosen: usize, accepted: bool, improved: bool) {
+    for a in arms.iter_mut() {
+        a.recent_tried *= DISCOUNT;
+        a.recent_improved *= DISCOUNT;
+    }
+    let a = &mut arms[chosen];
+    a.tried += 1;
+    a.accepted += accepted as u64;
+    a.improved += improved as u64;
+    a.recent_tried += 1.0;
+    a.recent_improved += improved as u64 as f64;
+}
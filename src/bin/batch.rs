@@ -0,0 +1,220 @@
+use ahc019::{
+    evaluate, run_parallel, summary_score, Budget, EvalRequest, EvalResponse, ParamTable,
+    Selection, SolveInput,
+};
+use clap::Parser;
+use serde::Serialize;
+use std::{
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// Runs many cases locally and prints the responses as a JSON array, like the state machine.
+#[derive(Parser, Debug)]
+struct Cli {
+    /// Number of generated cases (seeds `seed`, `seed + 1`, ...)
+    #[clap(short = 'n', default_value_t = 100)]
+    n: u64,
+    /// First seed for `tools::gen`
+    #[clap(long = "seed", default_value_t = 1001)]
+    seed: u64,
+    /// Fix D of the generated cases
+    #[clap(short = 'd')]
+    d: Option<usize>,
+    /// Read every *.txt in this directory (e.g. input/8) instead of generating; the seed of a
+    /// case is the number in its file name (e.g. 0003.txt is 3), or its index if there is none
+    #[clap(long = "dir")]
+    dir: Option<PathBuf>,
+    /// Number of worker threads (default: all cores)
+    #[clap(long = "threads")]
+    threads: Option<usize>,
+    /// Time limit per case in milliseconds
+    #[clap(long = "time-ms", default_value_t = 5800)]
+    time_ms: u64,
+    /// Step limit per case instead of the time limit
+    #[clap(long = "steps")]
+    steps: Option<u64>,
+    /// JSON parameter table to use instead of the embedded src/params.json
+    #[clap(long = "params")]
+    params: Option<PathBuf>,
+    /// `McParams::mc_run` (default: from the parameter table)
+    #[clap(long = "mc-run", short = 'R')]
+    mc_run: Option<u64>,
+    #[clap(long = "erase-small-th")]
+    erase_small_th: Option<usize>,
+    #[clap(long = "erase-shared-p")]
+    erase_shared_p: Option<f64>,
+    /// Weight of the `GrowShared` move (`EraseRefill` keeps the table's weight, usually 1)
+    #[clap(long = "grow-shared-weight")]
+    grow_shared_weight: Option<f64>,
+    /// Weight of the `RegionErase` move (`EraseRefill` keeps the table's weight, usually 1)
+    #[clap(long = "region-erase-weight")]
+    region_erase_weight: Option<f64>,
+    /// Pick moves and erase parameters with the bandit (`Selection::Bandit`)
+    #[clap(long = "bandit")]
+    bandit: bool,
+    /// Anneal from this temperature down to `--end-temp` (default: the table's acceptance)
+    #[clap(long = "start-temp")]
+    start_temp: Option<f64>,
+    #[clap(long = "end-temp", default_value_t = 1e-4)]
+    end_temp: f64,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let cases: Vec<(u64, Option<PathBuf>)> = match &cli.dir {
+        Some(dir) => {
+            let mut paths: Vec<_> = std::fs::read_dir(dir)
+                .unwrap_or_else(|e| {
+                    eprintln!("{}: {}", dir.display(), e);
+                    std::process::exit(1)
+                })
+                .map(|e| e.unwrap().path())
+                .filter(|p| p.extension().is_some_and(|e| e == "txt"))
+                .collect();
+            paths.sort();
+            paths
+                .into_iter()
+                .enumerate()
+                .map(|(i, p)| {
+                    let seed = p.file_stem().and_then(|s| s.to_str()?.parse().ok());
+                    (seed.unwrap_or(i as u64), Some(p))
+                })
+                .collect()
+        }
+        None => (cli.seed..cli.seed + cli.n)
+            .map(|seed| (seed, None))
+            .collect(),
+    };
+
+    let table = match &cli.params {
+        Some(path) => ParamTable::load(path).unwrap_or_else(|e| {
+            eprintln!("{}: {}", path.display(), e);
+            std::process::exit(1)
+        }),
+        None => ParamTable::embedded().clone(),
+    };
+    let threads = cli
+        .threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    let results = run_parallel(cases.len(), threads, |i| {
+        let (seed, path) = &cases[i];
+        // 1 ケースが落ちても他のケースの結果は残す
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            run(&cli, &table, *seed, path.as_deref())
+        }))
+        .unwrap_or_else(|e| {
+            let message = match e.downcast_ref::<String>() {
+                Some(s) => s.clone(),
+                None => e.downcast_ref::<&str>().unwrap_or(&"?").to_string(),
+            };
+            Err(Failure::new(*seed, format!("panicked: {}", message)))
+        })
+    });
+    let ok = results.iter().filter_map(|r| r.as_ref().ok());
+    match summary_score(ok.map(|r| (r.score, r.run_count))) {
+        Some((mean, log_mean, run_count)) => eprintln!("{} {} {}", mean, log_mean, run_count),
+        None => eprintln!("no cases"),
+    }
+    let failures: Vec<_> = results.iter().filter_map(|r| r.as_ref().err()).collect();
+    for f in failures.iter() {
+        eprintln!("seed {}: {}", f.seed, f.error);
+    }
+    let rows: Vec<_> = results
+        .iter()
+        .map(|r| match r {
+            Ok(response) => serde_json::to_value(response),
+            Err(failure) => serde_json::to_value(failure),
+        })
+        .collect::<Result<_, _>>()
+        .unwrap();
+    println!("{}", serde_json::to_string(&rows).unwrap());
+    if !failures.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+/// A case that could not be solved or whose answer did not check out, in place of its
+/// response in the output.
+#[derive(Debug, Serialize)]
+struct Failure {
+    seed: u64,
+    error: String,
+    /// 解いたが検証に通らなかったときの結果
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response: Option<Box<EvalResponse>>,
+}
+
+impl Failure {
+    fn new(seed: u64, error: String) -> Failure {
+        Failure {
+            seed,
+            error,
+            response: None,
+        }
+    }
+}
+
+fn run(
+    cli: &Cli,
+    table: &ParamTable,
+    seed: u64,
+    path: Option<&Path>,
+) -> Result<EvalResponse, Failure> {
+    let start = Instant::now();
+    let tools_input = match path {
+        Some(path) => {
+            let s = std::fs::read_to_string(path)
+                .map_err(|e| Failure::new(seed, format!("{}: {}", path.display(), e)))?;
+            tools::parse_input(&s)
+        }
+        None => tools::gen(seed, cli.d),
+    };
+    let tools_input = &tools_input;
+    let mut input =
+        SolveInput::from_tools_input(tools_input).map_err(|e| Failure::new(seed, e.to_string()))?;
+    input.start = start;
+    input.budget = match cli.steps {
+        Some(steps) => Budget::steps(steps),
+        None => Budget::time(Duration::from_millis(cli.time_ms)),
+    };
+    // 表の値をそのまま使い、指定されたものだけ差し替える
+    let mut params = table.get(input.d());
+    if let Some(mc_run) = cli.mc_run {
+        params.mc_run = mc_run;
+    }
+    if let Some(th) = cli.erase_small_th {
+        params.erase_small_th = th;
+    }
+    if let Some(p) = cli.erase_shared_p {
+        params.erase_shared_p = p;
+    }
+    if let Some(w) = cli.grow_shared_weight {
+        params.moves.grow_shared = w;
+    }
+    if let Some(w) = cli.region_erase_weight {
+        params.moves.region_erase = w;
+    }
+    if cli.bandit {
+        params.selection = Selection::Bandit;
+    }
+    if let Some(start_temp) = cli.start_temp {
+        params = params.annealing(start_temp, cli.end_temp);
+    }
+    let request = EvalRequest::new(seed, tools_input.d, &params);
+    let (response, best) = evaluate(request, input);
+    let (score, err) = tools::compute_score(tools_input, &best.to_output());
+    let error = if !err.is_empty() {
+        err
+    } else if (score as f64 - best.score * 1e9).abs() >= 1.0 {
+        format!("score {} does not match {}", score, best.score * 1e9)
+    } else {
+        return Ok(response);
+    };
+    Err(Failure {
+        seed,
+        error,
+        response: Some(Box::new(response)),
+    })
+}
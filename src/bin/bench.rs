@@ -1,7 +1,6 @@
-use ahc019::{mc_solve, McParams, SolveInput};
-use proconio::{input, marker::Bytes, source::auto::AutoSource};
+use ahc019::{mc_solve, Budget, SolveInput};
 use rand_pcg::Mcg128Xsl64;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 fn main() {
     let data = [
@@ -19,32 +18,20 @@ fn main() {
     ];
     let mut rng = Mcg128Xsl64::new(1);
     for &data in data.iter() {
-        let src = AutoSource::new(data);
-        input! {
-            from src,
-            d: u8,
-            front1: [Bytes; d],
-            right1: [Bytes; d],
-            front2: [Bytes; d],
-            right2: [Bytes; d],
+        let data = std::str::from_utf8(data).unwrap();
+        let mut intput = SolveInput::parse(data).unwrap();
+        let d = intput.d();
+        intput.budget = Budget::time(Duration::from_millis(1000));
+        intput.params.mc_run /= 6;
+        if intput.params.mc_run == 0 {
+            intput.params.mc_run = 1;
         }
-        let mut params = McParams::opt(d);
-        params.mc_run /= 6;
-        if params.mc_run == 0 {
-            params.mc_run = 1;
-        }
-        let intput = SolveInput {
-            start: Instant::now(),
-            limit: Duration::from_millis(1000),
-            front1,
-            right1,
-            front2,
-            right2,
-            params,
-        };
         let r = mc_solve(&mut rng, &intput, d);
-        assert!(r.g1.len() < 10000);
-        assert!(r.g2.len() < 10000);
+        assert!(r.grids[0].len() < 10000);
+        assert!(r.grids[1].len() < 10000);
         assert!(r.score > 0.0);
+        let input = tools::parse_input(data);
+        let (score, err) = tools::compute_score(&input, &r.to_output());
+        assert!(score > 0, "{}", err);
     }
 }
@@ -1,29 +1,26 @@
-use ahc019::{mc_solve, McParams, SolveInput};
-use proconio::{input, marker::Bytes};
+use ahc019::{mc_solve, Budget, SolveInput};
 use rand_pcg::Mcg128Xsl64;
-use std::time::{Duration, Instant};
+use std::{
+    io,
+    time::{Duration, Instant},
+};
 
 fn main() {
     let start = Instant::now();
-    input! {
-        d: u8,
-        front1: [Bytes; d],
-        right1: [Bytes; d],
-        front2: [Bytes; d],
-        right2: [Bytes; d],
+    let mut input = SolveInput::from_reader(io::stdin()).unwrap();
+    let d = input.d();
+    input.start = start;
+    input.budget = Budget::time(Duration::from_millis(200));
+    // `local_run_bench anneal <start_temp> <end_temp>` で焼きなましと比較する
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() == 4 && args[1] == "anneal" {
+        input.params = input
+            .params
+            .annealing(args[2].parse().unwrap(), args[3].parse().unwrap());
     }
-    let mut params = McParams::opt(d);
-    params.mc_run = 1;
-    let input = SolveInput {
-        start,
-        limit: Duration::from_millis(200),
-        front1,
-        right1,
-        front2,
-        right2,
-        params: McParams::opt(d),
-    };
     let mut rng = Mcg128Xsl64::new(3456);
-    let result = mc_solve(&mut rng, &input, d);
+    let mut result = mc_solve(&mut rng, &input, d);
+    result.polish(&input);
+    result.prune();
     println!("{}\t{}", (result.run_count as f64).ln(), result.score.ln());
 }
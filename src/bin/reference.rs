@@ -0,0 +1,77 @@
+use ahc019::{mc_solve_checkpointed, Budget, Snapshot, SolveInput};
+use rand_pcg::Mcg128Xsl64;
+use std::{
+    fs,
+    io::{self, BufWriter},
+    time::Duration,
+};
+
+const USAGE: &str =
+    "usage: reference <checkpoint.json> [--resume] [--secs N] [--interval N] < in.txt > out.txt";
+
+fn save(path: &str, snapshot: &Snapshot) {
+    // 書き込み途中で落ちても前回のものが残るようにする
+    let tmp = format!("{}.tmp", path);
+    fs::write(&tmp, serde_json::to_string(snapshot).unwrap()).unwrap();
+    fs::rename(&tmp, path).unwrap();
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1)
+}
+
+// 1 ケースに長時間かけて参照解を作る
+fn main() {
+    let mut path = None;
+    let mut resume = false;
+    let mut secs = 60;
+    let mut interval = 10;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--resume" => resume = true,
+            "--secs" => {
+                secs = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_else(|| exit_with(USAGE))
+            }
+            "--interval" => {
+                interval = args
+                    .next()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_else(|| exit_with(USAGE))
+            }
+            _ => path = Some(arg),
+        }
+    }
+    let path = path.unwrap_or_else(|| exit_with(USAGE));
+
+    let mut input =
+        SolveInput::from_reader(io::stdin()).unwrap_or_else(|e| exit_with(&e.to_string()));
+    let d = input.d();
+    input.budget = Budget::time(Duration::from_secs(secs));
+    let snapshot = if resume {
+        let json = fs::read_to_string(&path).unwrap_or_else(|e| exit_with(&e.to_string()));
+        Some(serde_json::from_str(&json).unwrap_or_else(|e| exit_with(&e.to_string())))
+    } else {
+        None
+    };
+
+    let mut rng = Mcg128Xsl64::new(9085);
+    let mut result = mc_solve_checkpointed(
+        &mut rng,
+        &input,
+        d,
+        snapshot,
+        Duration::from_secs(interval),
+        &mut |snapshot| save(&path, snapshot),
+    );
+    result.polish(&input);
+    result.prune();
+    eprintln!("{} {}", result.run_count, result.score);
+    result
+        .write_ans(&mut BufWriter::new(io::stdout().lock()))
+        .unwrap();
+}
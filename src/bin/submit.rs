@@ -1,56 +1,42 @@
-use ahc019::{mc_solve, McParams, SolveInput};
-use proconio::{input, marker::Bytes};
+use ahc019::{brute_force, is_tiny, mc_solve, BruteForceParams, SolveInput};
 use rand_pcg::Mcg128Xsl64;
 use std::{
-    collections::HashMap,
+    io::{self, BufWriter},
     time::{Duration, Instant},
 };
 
-fn print_v(v: &[u16], block_id_map: &HashMap<u16, usize>) {
-    for (i, g) in v.iter().enumerate() {
-        if i != 0 {
-            print!(" ");
-        }
-        print!("{}", block_id_map.get(g).unwrap_or(&0));
-    }
-    println!();
-}
-
-fn print_ans(g1: &[u16], g2: &[u16]) {
-    let mut block_id_map = HashMap::new();
-    for &g in g1.iter().chain(g2.iter()) {
-        if g == 0 || g == !0 {
-            continue;
-        }
-        let id = block_id_map.len() + 1;
-        block_id_map.entry(g).or_insert(id);
-    }
-    println!("{}", block_id_map.len());
-    print_v(g1, &block_id_map);
-    print_v(g2, &block_id_map);
-}
-
 fn main() {
     let start = Instant::now();
-    input! {
-        d: u8,
-        front1: [Bytes; d],
-        right1: [Bytes; d],
-        front2: [Bytes; d],
-        right2: [Bytes; d],
-    }
-    let params = McParams::opt(d);
-    let input = SolveInput {
-        start,
-        limit: Duration::from_millis(5800),
-        front1,
-        right1,
-        front2,
-        right2,
-        params,
-    };
+    let mut input = SolveInput::from_reader(io::stdin()).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1)
+    });
+    input.start = start;
+    let d = input.d();
     let mut rng = Mcg128Xsl64::new(9085);
-    let result = mc_solve(&mut rng, &input, d);
+    let mut result = if is_tiny(&input) {
+        let params = BruteForceParams {
+            min_block_size: 1,
+            upper_bound: 1e100,
+            limit: Duration::from_millis(2000),
+            ..Default::default()
+        };
+        let found = brute_force(&input, d, params);
+        // 展開した状態数は MC のステップ数とは別に出す
+        eprintln!(
+            "brute_force: {} states, finished: {}, score: {}",
+            found.expanded, found.finished, found.result.score
+        );
+        let mut result = found.result;
+        result.merge(mc_solve(&mut rng, &input, d));
+        result
+    } else {
+        mc_solve(&mut rng, &input, d)
+    };
+    result.polish(&input);
+    result.prune();
     eprintln!("{} {}", result.run_count, result.score);
-    print_ans(&result.g1, &result.g2);
+    result
+        .write_ans(&mut BufWriter::new(io::stdout().lock()))
+        .unwrap();
 }
@@ -0,0 +1,270 @@
+use ahc019::{
+    evaluate, run_parallel, summary_score, Budget, EvalRequest, McParams, ParamEntry, ParamTable,
+    SolveInput,
+};
+use clap::Parser;
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use rand_pcg::Mcg128Xsl64;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::OpenOptions,
+    io,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// Searches `McParams` for one D with local evaluations, appending each trial to `store`.
+#[derive(Parser, Debug)]
+struct Cli {
+    /// D of the generated cases
+    #[clap(short = 'd')]
+    d: Option<usize>,
+    /// Number of cases per trial (seeds `seed`, `seed + 1`, ...)
+    #[clap(short = 'n', default_value_t = 20)]
+    n: u64,
+    /// First seed for `tools::gen`
+    #[clap(long = "seed", default_value_t = 1001)]
+    seed: u64,
+    /// Number of trials to add
+    #[clap(long = "trials", default_value_t = 100)]
+    trials: usize,
+    /// JSON lines file of past trials; trials with the same d, n, seed and budget are resumed
+    #[clap(long = "store", default_value = "tune.jsonl")]
+    store: PathBuf,
+    /// Number of worker threads (default: all cores)
+    #[clap(long = "threads")]
+    threads: Option<usize>,
+    /// Time limit per case in milliseconds
+    #[clap(long = "time-ms", default_value_t = 5800)]
+    time_ms: u64,
+    /// Step limit per case instead of the time limit
+    #[clap(long = "steps")]
+    steps: Option<u64>,
+    /// Only print the best parameters for each D in `store`
+    #[clap(long = "table")]
+    table: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Trial {
+    d: usize,
+    n: u64,
+    seed: u64,
+    budget: Budget,
+    params: McParams,
+    score: f64,
+    /// 最小化する値
+    log_score: f64,
+    run_count: f64,
+}
+
+/// One searched parameter. A new parameter only needs a row in `SPACE`.
+struct Dim {
+    name: &'static str,
+    lo: f64,
+    hi: f64,
+    integer: bool,
+    get: fn(&McParams) -> f64,
+    set: fn(&mut McParams, f64),
+}
+
+// run_opt.py と同じ範囲
+const SPACE: &[Dim] = &[
+    Dim {
+        name: "mc_run",
+        lo: 1.0,
+        hi: 100.0,
+        integer: true,
+        get: |p| p.mc_run as f64,
+        set: |p, v| p.mc_run = v as u64,
+    },
+    Dim {
+        name: "erase_small_th",
+        lo: 0.0,
+        hi: 60.0,
+        integer: true,
+        get: |p| p.erase_small_th as f64,
+        set: |p, v| p.erase_small_th = v as usize,
+    },
+    Dim {
+        name: "erase_shared_p",
+        lo: 0.0,
+        hi: 1.0,
+        integer: false,
+        get: |p| p.erase_shared_p,
+        set: |p, v| p.erase_shared_p = v,
+    },
+    Dim {
+        name: "grow_shared_weight",
+        lo: 0.0,
+        hi: 1.0,
+        integer: false,
+        get: |p| p.moves.grow_shared,
+        set: |p, v| p.moves.grow_shared = v,
+    },
+    Dim {
+        name: "region_erase_weight",
+        lo: 0.0,
+        hi: 1.0,
+        integer: false,
+        get: |p| p.moves.region_erase,
+        set: |p, v| p.moves.region_erase = v,
+    },
+];
+
+impl Dim {
+    fn put(&self, params: &mut McParams, v: f64) {
+        let v = v.clamp(self.lo, self.hi);
+        (self.set)(params, if self.integer { v.round() } else { v });
+    }
+}
+
+/// Half the time a uniform sample, otherwise a Gaussian step around the best trial so far.
+fn suggest(rng: &mut Mcg128Xsl64, d: usize, best: Option<&Trial>) -> McParams {
+    let best = match best {
+        // 最初は今の表の値を測る
+        None => return McParams::opt(d as u8),
+        Some(best) => best.params,
+    };
+    let mut params = best;
+    let uniform = rng.gen_bool(0.5);
+    for dim in SPACE {
+        let v = if uniform {
+            rng.gen_range(dim.lo, dim.hi)
+        } else {
+            let sigma = 0.1 * (dim.hi - dim.lo);
+            Normal::new((dim.get)(&best), sigma).unwrap().sample(rng)
+        };
+        dim.put(&mut params, v);
+    }
+    params
+}
+
+/// Scores `params` on the cases of a trial with `evaluate`, as `batch` and `lambda` do.
+fn run_trial(cli: &Cli, d: usize, budget: Budget, params: McParams) -> Trial {
+    let threads = cli
+        .threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    let results = run_parallel(cli.n as usize, threads, |i| {
+        let seed = cli.seed + i as u64;
+        let tools_input = tools::gen(seed, Some(d));
+        let start = Instant::now();
+        let mut input = SolveInput::from_tools_input(&tools_input).unwrap();
+        input.start = start;
+        input.budget = budget;
+        let (response, _) = evaluate(EvalRequest::new(seed, d, &params), input);
+        (response.score, response.run_count)
+    });
+    // `main` で n > 0 を確かめている
+    let (score, log_score, run_count) = summary_score(results).unwrap();
+    Trial {
+        d,
+        n: cli.n,
+        seed: cli.seed,
+        budget,
+        params,
+        score,
+        log_score,
+        run_count,
+    }
+}
+
+/// Trials in `path`, none if it does not exist yet.
+fn load(path: &Path) -> Result<Vec<Trial>, String> {
+    let s = match std::fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(format!("{}: {}", path.display(), e)),
+    };
+    s.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| format!("{}:{}: {}", path.display(), i + 1, e))
+        })
+        .collect()
+}
+
+/// Prints the best trial of each D as a `ParamTable`, ready for src/params.json.
+fn print_table(trials: &[Trial]) {
+    let mut best = BTreeMap::new();
+    for t in trials {
+        let e = best.entry(t.d).or_insert(t);
+        if t.log_score < e.log_score {
+            *e = t;
+        }
+    }
+    let entries = best
+        .values()
+        .map(|t| ParamEntry {
+            d: t.d as u8,
+            log_score: Some(t.log_score),
+            params: t.params,
+        })
+        .collect();
+    match ParamTable::new(entries) {
+        Ok(table) => println!("{}", table.to_json()),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let mut trials = load(&cli.store).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1)
+    });
+    if cli.table {
+        print_table(&trials);
+        return;
+    }
+    let d = cli.d.unwrap_or_else(|| {
+        eprintln!("-d is required unless --table is given");
+        std::process::exit(1)
+    });
+    if cli.n == 0 {
+        eprintln!("-n must be positive");
+        std::process::exit(1)
+    }
+    let budget = match cli.steps {
+        Some(steps) => Budget::steps(steps),
+        None => Budget::time(Duration::from_millis(cli.time_ms)),
+    };
+    trials.retain(|t| t.d == d && t.n == cli.n && t.seed == cli.seed && t.budget == budget);
+    let mut best = trials
+        .iter()
+        .min_by(|a, b| a.log_score.total_cmp(&b.log_score))
+        .cloned();
+    // 再開しても同じ列を引くように、試行回数から乱数を作る
+    let mut rng = Mcg128Xsl64::new(trials.len() as u128 + 1);
+    let mut store = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&cli.store)
+        .unwrap();
+    for k in trials.len()..trials.len() + cli.trials {
+        let params = suggest(&mut rng, d, best.as_ref());
+        let trial = run_trial(&cli, d, budget, params);
+        writeln!(store, "{}", serde_json::to_string(&trial).unwrap()).unwrap();
+        if best.as_ref().is_none_or(|b| trial.log_score < b.log_score) {
+            best = Some(trial.clone());
+        }
+        let params: Vec<_> = SPACE
+            .iter()
+            .map(|dim| format!("{}={}", dim.name, (dim.get)(&trial.params)))
+            .collect();
+        eprintln!(
+            "trial {}: {} {} {} {} (best {})",
+            k,
+            params.join(" "),
+            trial.score,
+            trial.log_score,
+            trial.run_count,
+            best.as_ref().unwrap().log_score
+        );
+    }
+    print_table(&best.into_iter().collect::<Vec<_>>());
+}
@@ -1,98 +1,201 @@
 use crate::Point;
 use rand::Rng;
 use rand_pcg::Mcg128Xsl64;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone)]
+/// 共有ブロックの id は 1 から、片側だけのブロックの id はここから振る
+const FIRST_HALF_ID: u16 = 10000;
+
+/// `BlockSet` への変更を取り消すための記録
+#[derive(Debug, Clone, PartialEq)]
+enum Change {
+    PushShared,
+    PopShared(usize, (u16, Vec<Vec<Point>>)),
+    TakeStock(u16),
+    NextHalfId,
+    PushHalf(usize),
+    HalfReset(Vec<Vec<Point>>, u16),
+    /// 共有ブロック i の使っている物体すべてに 1 セルずつ足した
+    GrowShared(usize),
+    TakeHalf(usize, usize, Point),
+}
+
+/// Whether `id` is the id of a block placed in one object only.
+pub fn is_half_id(id: u16) -> bool {
+    (FIRST_HALF_ID..!0).contains(&id)
+}
+
+/// Blocks placed in the objects of a `GridSystem`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BlockSet {
-    pub shared: Vec<(u16, Vec<Point>, Vec<Point>)>,
+    /// 共有ブロックの id と物体ごとのセル。使わない物体のセルは空
+    pub shared: Vec<(u16, Vec<Vec<Point>>)>,
     shared_id_stock: Vec<u16>,
-    pub half1: Vec<Point>,
-    pub half2: Vec<Point>,
+    /// 物体ごとの、片側だけのブロックのセル
+    pub half: Vec<Vec<Point>>,
     next_half_id: u16,
+    #[serde(skip)]
+    journal: Vec<Change>,
+}
+
+/// Number of cubes in a shared block, the same in every object that uses it.
+pub fn shared_size(cells: &[Vec<Point>]) -> usize {
+    cells.iter().map(|c| c.len()).max().unwrap_or(0)
 }
 
-impl Default for BlockSet {
-    fn default() -> Self {
+impl BlockSet {
+    /// An empty set for `n` objects.
+    pub fn new(n: usize) -> BlockSet {
         BlockSet {
             shared: Vec::new(),
             shared_id_stock: Vec::new(),
-            half1: Vec::new(),
-            half2: Vec::new(),
-            next_half_id: 10000,
+            half: vec![Vec::new(); n],
+            next_half_id: FIRST_HALF_ID,
+            journal: Vec::new(),
         }
     }
-}
-
-impl BlockSet {
-    pub fn new() -> BlockSet {
-        Default::default()
-    }
 
     pub fn reset(&mut self) {
         self.shared.clear();
         self.shared_id_stock.clear();
-        self.half1.clear();
-        self.half2.clear();
-        self.next_half_id = 10000;
+        for half in self.half.iter_mut() {
+            half.clear();
+        }
+        self.next_half_id = FIRST_HALF_ID;
+        self.journal.clear();
+    }
+
+    /// Forget the changes recorded since the last commit/rollback.
+    pub fn commit(&mut self) {
+        self.journal.clear();
+    }
+
+    /// Undo every change recorded since the last commit/rollback.
+    pub fn rollback(&mut self) {
+        while let Some(change) = self.journal.pop() {
+            match change {
+                Change::PushShared => {
+                    self.shared.pop();
+                }
+                Change::PopShared(i, block) => {
+                    self.shared_id_stock.pop();
+                    self.shared.push(block);
+                    let last = self.shared.len() - 1;
+                    self.shared.swap(i, last);
+                }
+                Change::TakeStock(id) => self.shared_id_stock.push(id),
+                Change::NextHalfId => self.next_half_id -= 1,
+                Change::PushHalf(object) => {
+                    self.half[object].pop();
+                }
+                Change::HalfReset(half, next_half_id) => {
+                    self.half = half;
+                    self.next_half_id = next_half_id;
+                }
+                Change::GrowShared(i) => {
+                    for cells in self.shared[i].1.iter_mut() {
+                        cells.pop();
+                    }
+                }
+                Change::TakeHalf(object, i, p) => {
+                    let half = &mut self.half[object];
+                    half.push(p);
+                    let last = half.len() - 1;
+                    half.swap(i, last);
+                }
+            }
+        }
     }
 
     pub fn shared_only_score(&self) -> f64 {
         let mut score = 0.0;
-        for (_, v, _) in self.shared.iter() {
-            score += 1.0 / v.len() as f64;
+        for (_, cells) in self.shared.iter() {
+            score += 1.0 / shared_size(cells) as f64;
         }
         score
     }
 
     pub fn gen_shared_block_id(&mut self) -> u16 {
         if let Some(id) = self.shared_id_stock.pop() {
+            self.journal.push(Change::TakeStock(id));
             id
         } else {
-            (self.shared.len() + 1) as u16
+            let id = self.shared.len() + 1;
+            assert!(id < FIRST_HALF_ID as usize, "too many shared blocks");
+            id as u16
         }
     }
 
     pub fn gen_half_block_id(&mut self) -> u16 {
         let id = self.next_half_id;
+        // !0 は置けないセルの印
+        assert!(id < !0, "too many half blocks");
         self.next_half_id += 1;
+        self.journal.push(Change::NextHalfId);
         id
     }
 
-    pub fn push_shared(&mut self, block_id: u16, p1: Vec<Point>, p2: Vec<Point>) {
-        self.shared.push((block_id, p1, p2));
+    pub fn push_shared(&mut self, block_id: u16, cells: Vec<Vec<Point>>) {
+        self.shared.push((block_id, cells));
+        self.journal.push(Change::PushShared);
     }
 
-    fn pop_shared(&mut self, i: usize) -> (Vec<Point>, Vec<Point>) {
-        let (id, s1, s2) = self.shared.swap_remove(i);
+    fn pop_shared(&mut self, i: usize) -> Vec<Vec<Point>> {
+        let (id, cells) = self.shared.swap_remove(i);
         self.shared_id_stock.push(id);
-        (s1, s2)
+        self.journal.push(Change::PopShared(i, (id, cells.clone())));
+        cells
+    }
+
+    pub fn pop_id(&mut self, id: u16) -> Option<Vec<Vec<Point>>> {
+        let i = self.shared.iter().position(|(i, _)| *i == id)?;
+        Some(self.pop_shared(i))
     }
 
-    pub fn pop_random(&mut self, rng: &mut Mcg128Xsl64) -> (Vec<Point>, Vec<Point>) {
+    pub fn pop_random(&mut self, rng: &mut Mcg128Xsl64) -> Vec<Vec<Point>> {
         let i = rng.gen_range(0, self.shared.len());
         self.pop_shared(i)
     }
 
-    pub fn pop_small(&mut self, th: usize) -> Option<(Vec<Point>, Vec<Point>)> {
+    pub fn pop_small(&mut self, th: usize) -> Option<Vec<Vec<Point>>> {
         for i in 0..self.shared.len() {
-            if self.shared[i].1.len() <= th {
+            if shared_size(&self.shared[i].1) <= th {
                 return Some(self.pop_shared(i));
             }
         }
         None
     }
 
-    pub fn push_half(&mut self, place: u8, p: Point) {
-        if place == 1 {
-            self.half1.push(p);
-        } else {
-            self.half2.push(p);
+    /// Adds one cell to shared block `i` in every object it uses, `cells` in object order.
+    pub fn grow_shared(&mut self, i: usize, cells: &[Point]) {
+        let mut cells = cells.iter();
+        for object_cells in self.shared[i].1.iter_mut() {
+            if !object_cells.is_empty() {
+                object_cells.push(*cells.next().unwrap());
+            }
         }
+        debug_assert!(cells.next().is_none());
+        self.journal.push(Change::GrowShared(i));
+    }
+
+    /// Forgets that `p` of `object` is in a half block, so that a shared block can take it.
+    pub fn take_half(&mut self, object: usize, p: Point) {
+        let half = &mut self.half[object];
+        let i = half.iter().position(|&q| q == p).unwrap();
+        half.swap_remove(i);
+        self.journal.push(Change::TakeHalf(object, i, p));
+    }
+
+    pub fn push_half(&mut self, object: usize, p: Point) {
+        self.half[object].push(p);
+        self.journal.push(Change::PushHalf(object));
     }
 
     pub fn half_reset(&mut self) {
-        self.half1.clear();
-        self.half2.clear();
-        self.next_half_id = 10000;
+        let n = self.half.len();
+        let half = std::mem::replace(&mut self.half, vec![Vec::new(); n]);
+        self.journal
+            .push(Change::HalfReset(half, self.next_half_id));
+        self.next_half_id = FIRST_HALF_ID;
     }
 }
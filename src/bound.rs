@@ -0,0 +1,106 @@
+use crate::SolveInput;
+
+/// Cube counts of one object that hold for every valid answer.
+struct Volume {
+    /// 各 z 層でシルエットを満たすのに必要な最小個数の合計
+    min: usize,
+    /// シルエットの内側にあるセルの数
+    max: usize,
+    /// シルエットの内側にある最大の連結成分の大きさ
+    max_block: usize,
+}
+
+fn volume(front: &[Vec<u8>], right: &[Vec<u8>]) -> Volume {
+    let d = front.len();
+    let mut min = 0;
+    let mut max = 0;
+    for z in 0..d {
+        let f = front[z].iter().filter(|&&c| c == b'1').count();
+        let r = right[z].iter().filter(|&&c| c == b'1').count();
+        min += f.max(r);
+        max += f * r;
+    }
+    let allowed = |x: usize, y: usize, z: usize| front[z][x] == b'1' && right[z][y] == b'1';
+    let mut visited = vec![false; d * d * d];
+    let mut max_block = 0;
+    for x in 0..d {
+        for y in 0..d {
+            for z in 0..d {
+                if !allowed(x, y, z) || visited[(x * d + y) * d + z] {
+                    continue;
+                }
+                visited[(x * d + y) * d + z] = true;
+                let mut size = 0;
+                let mut stack = vec![(x, y, z)];
+                while let Some((x, y, z)) = stack.pop() {
+                    size += 1;
+                    for &(dx, dy, dz) in tools::D3.iter() {
+                        let (x, y, z) =
+                            (x.wrapping_add(dx), y.wrapping_add(dy), z.wrapping_add(dz));
+                        if x < d && y < d && z < d && allowed(x, y, z) {
+                            let i = (x * d + y) * d + z;
+                            if !visited[i] {
+                                visited[i] = true;
+                                stack.push((x, y, z));
+                            }
+                        }
+                    }
+                }
+                max_block = max_block.max(size);
+            }
+        }
+    }
+    Volume {
+        min,
+        max,
+        max_block,
+    }
+}
+
+/// A score no valid answer for `input` can beat.
+///
+/// With `s` cubes of each object in shared blocks, object `i` still needs at least
+/// `min_i - s` unshared cubes (1 point each). A shared block lies in at least two objects, so
+/// it has at most the second largest `max_block` cubes, and the shared blocks cost at least
+/// `k^2 / s` with `k = ceil(s / max_block)` blocks. No object can have more than `max_i`
+/// shared cubes, nor more than the others have together.
+///
+/// The bound ignores that a shared block must have the same shape in every object it lies in,
+/// so it is far below real scores (around 0.02 against 1 to 3 at D = 7). Use it as a sanity
+/// check, not as a measure of how far a score is from optimal.
+pub fn lower_bound(input: &SolveInput) -> f64 {
+    let volumes: Vec<_> = input
+        .objects
+        .iter()
+        .map(|o| volume(&o.front, &o.right))
+        .collect();
+    let mut blocks: Vec<_> = volumes.iter().map(|v| v.max_block).collect();
+    blocks.sort_unstable();
+    let max_block = if blocks.len() < 2 {
+        0
+    } else {
+        blocks[blocks.len() - 2]
+    };
+    let total: usize = volumes.iter().map(|v| v.max).sum();
+    let max_shared = if max_block == 0 {
+        0
+    } else {
+        volumes
+            .iter()
+            .map(|v| v.max.min(total - v.max))
+            .max()
+            .unwrap_or(0)
+    };
+    (0..=max_shared)
+        .map(|s| {
+            let unshared: usize = volumes.iter().map(|v| v.min.saturating_sub(s)).sum();
+            let shared = if s == 0 {
+                0.0
+            } else {
+                let k = s.div_ceil(max_block) as f64;
+                k * k / s as f64
+            };
+            shared + unshared as f64
+        })
+        .fold(f64::INFINITY, f64::min)
+}
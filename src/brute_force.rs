@@ -1,5 +1,29 @@
-use crate::{AxisMap, Grid3, GridFront, GridRight, Point};
+use crate::{AxisMap, Grid3, GridFront, GridRight, Point, SolveInput, SolveResult};
 use rustc_hash::{FxHashMap, FxHashSet};
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone)]
+pub struct BruteForceParams {
+    /// Shared blocks with fewer cubes than this are never tried.
+    pub min_block_size: usize,
+    /// Only solutions scoring below this are searched for.
+    pub upper_bound: f64,
+    /// Wall-clock budget, measured from `SolveInput::start`.
+    pub limit: Duration,
+    /// Memory budget as the number of remembered states.
+    pub max_states: usize,
+}
+
+impl Default for BruteForceParams {
+    fn default() -> Self {
+        BruteForceParams {
+            min_block_size: 5,
+            upper_bound: 0.5,
+            limit: Duration::from_millis(1000),
+            max_states: 1 << 20,
+        }
+    }
+}
 
 #[derive(Copy, Clone, Eq, PartialEq)]
 enum FaceState {
@@ -11,18 +35,22 @@ enum FaceState {
 #[derive(Clone)]
 struct GridBox {
     d: u8,
-    grid: Grid3<u8>,
+    grid: Grid3<u16>,
     front: GridFront<FaceState>,
     right: GridRight<FaceState>,
 }
 
-fn make_face(shadow: &[Vec<u8>]) -> Vec<FaceState> {
+fn make_face(shadow: &[Vec<u8>], t: bool) -> Vec<FaceState> {
     let d = shadow.len();
     let mut v = vec![FaceState::Null; d * d];
     for (i, row) in shadow.iter().enumerate() {
         for (j, &f) in row.iter().enumerate() {
             if f == b'1' {
-                v[j * d + i] = FaceState::Yet;
+                if t {
+                    v[i * d + j] = FaceState::Yet;
+                } else {
+                    v[j * d + i] = FaceState::Yet;
+                }
             }
         }
     }
@@ -32,8 +60,8 @@ fn make_face(shadow: &[Vec<u8>]) -> Vec<FaceState> {
 impl GridBox {
     pub fn new(d: u8, front: &[Vec<u8>], right: &[Vec<u8>]) -> GridBox {
         let mut grid = Grid3::new(d, 0);
-        let front = GridFront::from_vec(d, make_face(&front));
-        let right = GridRight::from_vec(d, make_face(&right));
+        let front = GridFront::from_vec(d, make_face(front, false));
+        let right = GridRight::from_vec(d, make_face(right, true));
         for x in 0..d {
             for y in 0..d {
                 for z in 0..d {
@@ -52,11 +80,12 @@ impl GridBox {
         }
     }
 
-    pub fn key(&self) -> u128 {
-        let mut k = 0;
+    /// Bitset of the free cells.
+    pub fn key(&self) -> Vec<u64> {
+        let mut k = vec![0; self.grid.data.len().div_ceil(64)];
         for (i, &g) in self.grid.data.iter().enumerate() {
             if g == 0 {
-                k ^= 1 << i;
+                k[i / 64] |= 1 << (i % 64);
             }
         }
         k
@@ -68,9 +97,8 @@ impl GridBox {
             for y in 0..self.d {
                 for z in 0..self.d {
                     let p = Point::new(x, y, z);
-                    match (self.front[p], self.right[p]) {
-                        (FaceState::Yet, FaceState::Yet) => v.push(p),
-                        _ => (),
+                    if let (FaceState::Yet, FaceState::Yet) = (self.front[p], self.right[p]) {
+                        v.push(p);
                     }
                 }
             }
@@ -83,7 +111,7 @@ impl GridBox {
             && self.right.data.iter().all(|&f| f != FaceState::Yet)
     }
 
-    pub fn put(&mut self, p: Point, block_id: u8) {
+    pub fn put(&mut self, p: Point, block_id: u16) {
         self.grid[p] = block_id;
         self.front[p] = FaceState::Satisfy;
         self.right[p] = FaceState::Satisfy;
@@ -99,10 +127,10 @@ struct StackItem {
 fn grow_shared_block(
     grid_1: &GridBox,
     grid_2: &GridBox,
-    block_id: u8,
+    block_id: u16,
     p1: Point,
     p2: Point,
-) -> Vec<(GridBox, GridBox, u8)> {
+) -> Vec<(GridBox, GridBox, usize)> {
     let d = grid_1.d;
     let mut grids = FxHashMap::default();
     grids.insert(AxisMap::new(), (grid_1.clone(), grid_2.clone(), 0));
@@ -143,43 +171,83 @@ fn grow_shared_block(
     grids.into_values().collect()
 }
 
-pub struct SolveResult {
-    pub g1: Vec<u16>,
-    pub g2: Vec<u16>,
-    pub score: f64,
+/// Number of cells allowed by both silhouettes.
+fn max_volume(front: &[Vec<u8>], right: &[Vec<u8>]) -> usize {
+    front
+        .iter()
+        .zip(right.iter())
+        .map(|(f, r)| {
+            f.iter().filter(|&&c| c == b'1').count() * r.iter().filter(|&&c| c == b'1').count()
+        })
+        .sum()
 }
 
-pub fn solve(
-    d: u8,
-    front1: &[Vec<u8>],
-    right1: &[Vec<u8>],
-    front2: &[Vec<u8>],
-    right2: &[Vec<u8>],
-) -> SolveResult {
-    let mut mem = FxHashSet::with_capacity_and_hasher(10000, Default::default());
+/// Whether the input is small enough for `brute_force` to be worth a try.
+///
+/// `brute_force` only handles two objects and panics on others.
+pub fn is_tiny(input: &SolveInput) -> bool {
+    input.objects.len() == 2
+        && input
+            .objects
+            .iter()
+            .all(|o| max_volume(&o.front, &o.right) <= 24)
+}
+
+/// What `brute_force` found and how far it got.
+#[derive(Debug, Clone)]
+pub struct BruteForceResult {
+    /// `run_count` is 0: no MC steps were made.
+    pub result: SolveResult,
+    /// Number of expanded states.
+    pub expanded: u32,
+    /// The search ran to its end within the budget. This does not make `result` optimal.
+    pub finished: bool,
+}
+
+/// Heuristic search for a cover of both objects of a two-object input by shared blocks,
+/// depth first. It is not an exact solver.
+///
+/// Each state tries every pair of cells still needed by the silhouettes and, for every
+/// orientation (`AxisMap` state), grows one greedy maximal block from them. Smaller blocks,
+/// other growth orders and unshared cubes are never tried, so a finished search can still
+/// miss the optimum, and an input that needs unshared cubes gets `SolveResult::worst()`.
+pub fn brute_force(input: &SolveInput, d: u8, params: BruteForceParams) -> BruteForceResult {
+    assert_eq!(
+        input.objects.len(),
+        2,
+        "brute_force only handles two objects"
+    );
+    let mut mem = FxHashSet::default();
     let mut grids = vec![(
-        GridBox::new(d, front1, right1),
-        GridBox::new(d, front2, right2),
+        GridBox::new(d, &input.objects[0].front, &input.objects[0].right),
+        GridBox::new(d, &input.objects[1].front, &input.objects[1].right),
         0.0,
         0,
     )];
-    let mut best = grids[0].clone();
-    best.2 = 0.5;
+    let mut best = None;
+    let mut best_score = params.upper_bound;
+    let mut expanded = 0;
+    let mut finished = true;
     while let Some((grid_1, grid_2, score, last_block_id)) = grids.pop() {
-        if score >= best.2 {
+        if input.start.elapsed() > params.limit || mem.len() > params.max_states {
+            finished = false;
+            break;
+        }
+        if score >= best_score {
             continue;
         }
+        expanded += 1;
         let block_id = last_block_id + 1;
         let pp1 = grid_1.make_can_points();
         let pp2 = grid_2.make_can_points();
         for &p1 in pp1.iter() {
             for &p2 in pp2.iter() {
                 for (grid_1, grid_2, c) in grow_shared_block(&grid_1, &grid_2, block_id, p1, p2) {
-                    if c < 5 {
+                    if c < params.min_block_size {
                         continue;
                     }
                     let new_score = score + 1.0 / c as f64;
-                    if new_score >= best.2 {
+                    if new_score >= best_score {
                         continue;
                     }
                     let key = (grid_1.key(), grid_2.key());
@@ -187,32 +255,23 @@ pub fn solve(
                         continue;
                     }
                     if grid_1.is_satisfy() && grid_2.is_satisfy() {
-                        if new_score < best.2 {
-                            best = (grid_1, grid_2, new_score, block_id);
-                        }
+                        best_score = new_score;
+                        best = Some((grid_1, grid_2));
                     } else {
                         grids.push((grid_1, grid_2, new_score, block_id));
                     }
                 }
             }
         }
-        eprintln!("{} {} {} {}", mem.len(), grids.len(), best.2, score);
     }
-    SolveResult {
-        g1: best
-            .0
-            .grid
-            .data
-            .iter()
-            .map(|&g| if g != !0 { g as u16 } else { !0 })
-            .collect(),
-        g2: best
-            .1
-            .grid
-            .data
-            .iter()
-            .map(|&g| if g != !0 { g as u16 } else { !0 })
-            .collect(),
-        score: best.2,
+    let mut result = SolveResult::worst();
+    if let Some((grid_1, grid_2)) = best {
+        result.grids = vec![grid_1.grid.data, grid_2.grid.data];
+        result.score = best_score;
+    }
+    BruteForceResult {
+        result,
+        expanded,
+        finished,
     }
 }
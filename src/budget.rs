@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// When to stop searching: wall-clock time, a number of MC steps, or whichever comes first.
+///
+/// A step-only budget never looks at the clock, so a fixed seed gives the same answer on
+/// any machine.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Budget {
+    pub time: Option<Duration>,
+    pub steps: Option<u64>,
+}
+
+impl Budget {
+    pub const fn time(limit: Duration) -> Budget {
+        Budget {
+            time: Some(limit),
+            steps: None,
+        }
+    }
+
+    pub const fn steps(steps: u64) -> Budget {
+        Budget {
+            time: None,
+            steps: Some(steps),
+        }
+    }
+
+    pub const fn both(limit: Duration, steps: u64) -> Budget {
+        Budget {
+            time: Some(limit),
+            steps: Some(steps),
+        }
+    }
+
+    /// The share of what is left for one of `rest_run` remaining restarts, or `None` when
+    /// nothing is left.
+    pub fn split(&self, elapsed: Duration, used_steps: u64, rest_run: u64) -> Option<Budget> {
+        let time = match self.time {
+            Some(limit) if limit <= elapsed => return None,
+            Some(limit) => {
+                let total_mill = (limit - elapsed).as_millis() as u64;
+                Some(Duration::from_millis(total_mill / rest_run))
+            }
+            None => None,
+        };
+        let steps = match self.steps {
+            Some(steps) if steps <= used_steps => return None,
+            Some(steps) => Some((steps - used_steps) / rest_run),
+            None => None,
+        };
+        Some(Budget { time, steps })
+    }
+
+    pub fn is_over(&self, elapsed: Duration, step: u64) -> bool {
+        matches!(self.time, Some(limit) if elapsed > limit)
+            || matches!(self.steps, Some(steps) if step >= steps)
+    }
+
+    /// How far through the budget a run is, in `[0, 1]`.
+    pub fn progress(&self, elapsed: Duration, step: u64) -> f64 {
+        let time = self
+            .time
+            .map_or(0.0, |limit| elapsed.as_secs_f64() / limit.as_secs_f64());
+        let steps = self.steps.map_or(0.0, |steps| step as f64 / steps as f64);
+        time.max(steps).min(1.0)
+    }
+}
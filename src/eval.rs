@@ -0,0 +1,156 @@
+use crate::{
+    lower_bound, mc_solve, Acceptance, McParams, MoveWeights, Schedule, Selection, SolveInput,
+    SolveResult,
+};
+use rand_pcg::Mcg128Xsl64;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// One evaluation job, in the shape the Step Functions state machine sends to `lambda`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EvalRequest {
+    pub seed: u64,
+    pub d: usize,
+    pub mc_run: u64,
+    pub erase_small_th: usize,
+    pub erase_shared_p: f64,
+    /// 古いリクエストにはないので 1
+    #[serde(default = "one")]
+    pub erase_refill_weight: f64,
+    #[serde(default)]
+    pub grow_shared_weight: f64,
+    #[serde(default)]
+    pub region_erase_weight: f64,
+    /// true なら `Selection::Bandit`
+    #[serde(default)]
+    pub bandit: bool,
+    /// 0 なら山登り
+    #[serde(default)]
+    pub start_temp: f64,
+    #[serde(default)]
+    pub end_temp: f64,
+    #[serde(default)]
+    pub schedule: Schedule,
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+impl EvalRequest {
+    /// The request whose `params()` are `params`.
+    pub fn new(seed: u64, d: usize, params: &McParams) -> EvalRequest {
+        let annealing = params.acceptance == Acceptance::Annealing;
+        EvalRequest {
+            seed,
+            d,
+            mc_run: params.mc_run,
+            erase_small_th: params.erase_small_th,
+            erase_shared_p: params.erase_shared_p,
+            erase_refill_weight: params.moves.erase_refill,
+            grow_shared_weight: params.moves.grow_shared,
+            region_erase_weight: params.moves.region_erase,
+            bandit: params.selection == Selection::Bandit,
+            start_temp: if annealing { params.start_temp } else { 0.0 },
+            end_temp: if annealing { params.end_temp } else { 0.0 },
+            schedule: params.schedule,
+        }
+    }
+
+    pub fn params(&self) -> McParams {
+        let params = McParams {
+            mc_run: self.mc_run,
+            erase_small_th: self.erase_small_th,
+            erase_shared_p: self.erase_shared_p,
+            moves: MoveWeights {
+                erase_refill: self.erase_refill_weight,
+                grow_shared: self.grow_shared_weight,
+                region_erase: self.region_erase_weight,
+            },
+            selection: if self.bandit {
+                Selection::Bandit
+            } else {
+                Selection::Weighted
+            },
+            schedule: self.schedule,
+            ..Default::default()
+        };
+        if self.start_temp > 0.0 {
+            params.annealing(self.start_temp, self.end_temp)
+        } else {
+            params
+        }
+    }
+}
+
+/// The result of one `EvalRequest`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EvalResponse {
+    pub request: EvalRequest,
+    /// The score of the answer after `polish` and `prune`, as `tools::compute_score` gives it
+    /// divided by 1e9. Older responses had the raw `mc_solve` score, which is higher, so the
+    /// two should not be mixed in one study.
+    pub score: f64,
+    pub run_count: u32,
+    pub lower_bound: f64,
+}
+
+/// Solves, polishes and prunes `input` with the parameters of `request`, keeping its `start` and `budget`.
+pub fn evaluate(request: EvalRequest, mut input: SolveInput) -> (EvalResponse, SolveResult) {
+    input.params = request.params();
+    let mut rng = Mcg128Xsl64::new(32343);
+    let mut best = mc_solve(&mut rng, &input, input.d());
+    best.polish(&input);
+    best.prune();
+    let response = EvalResponse {
+        request,
+        score: best.score,
+        run_count: best.run_count,
+        lower_bound: lower_bound(&input),
+    };
+    (response, best)
+}
+
+/// Calls `f(0)`, ..., `f(n - 1)` on `threads` workers and returns the results in order.
+pub fn run_parallel<T: Send>(n: usize, threads: usize, f: impl Fn(usize) -> T + Sync) -> Vec<T> {
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = vec![];
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= n {
+                            break done;
+                        }
+                        done.push((i, f(i)));
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    });
+    results.sort_by_key(|&(i, _)| i);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+/// Mean score, mean log score and mean `run_count`, as `summary_score` in common.py, or
+/// `None` when there are no results.
+pub fn summary_score(results: impl IntoIterator<Item = (f64, u32)>) -> Option<(f64, f64, f64)> {
+    let mut n = 0.0;
+    let (mut s, mut log_s, mut c) = (0.0, 0.0, 0.0);
+    for (score, run_count) in results {
+        n += 1.0;
+        s += score;
+        log_s += score.ln();
+        c += run_count as f64;
+    }
+    if n == 0.0 {
+        return None;
+    }
+    Some((s / n, log_s / n, c / n))
+}
@@ -1,22 +1,31 @@
+use serde::{Deserialize, Serialize};
 use smallvec::{smallvec, SmallVec};
-use std::ops::{Index, IndexMut};
+use std::{
+    convert::TryFrom,
+    ops::{Index, IndexMut},
+};
+// 回転は `tools::is_same` と共有する
+pub use tools::Rotation;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub struct Point(u8, u8, u8);
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawGrid<T>")]
 pub struct Grid3<T> {
     d: u8,
     pub data: Vec<T>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawGrid<T>")]
 pub struct GridFront<T> {
     d: u8,
     pub data: Vec<T>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawGrid<T>")]
 pub struct GridRight<T> {
     d: u8,
     pub data: Vec<T>,
@@ -28,6 +37,21 @@ impl Point {
         Point(x, y, z)
     }
 
+    #[inline(always)]
+    pub const fn xyz(self) -> (u8, u8, u8) {
+        (self.0, self.1, self.2)
+    }
+
+    /// Rotates `self` as an offset vector; negative coordinates wrap like `next_cell`.
+    pub fn rotate(self, rot: Rotation) -> Point {
+        let mut q = [0u8; 3];
+        for (axis, &c) in [self.0, self.1, self.2].iter().enumerate() {
+            let dir = rot.apply_dir(axis as u8 * 2);
+            q[dir as usize / 2] = if dir & 1 == 0 { c } else { c.wrapping_neg() };
+        }
+        Point(q[0], q[1], q[2])
+    }
+
     fn to_x(self, d: u8, dx: u8) -> Option<Point> {
         let x = self.0.wrapping_add(dx);
         if x < d {
@@ -68,11 +92,19 @@ impl Point {
     }
 }
 
+/// A rotation from object 1 to object 2 that is pinned down step by step while a shared
+/// block grows: nothing yet, one direction, or the whole rotation.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum AxisMap {
     None,
     Map1 { from: u8, to: u8 },
-    Map2 { map: [u8; 6] },
+    Map2 { rot: Rotation },
+}
+
+impl Default for AxisMap {
+    fn default() -> Self {
+        AxisMap::new()
+    }
 }
 
 impl AxisMap {
@@ -83,597 +115,11 @@ impl AxisMap {
     pub fn fix(self, d1: u8, d2: u8) -> AxisMap {
         match self {
             AxisMap::None => AxisMap::Map1 { from: d1, to: d2 },
-            AxisMap::Map1 { from, to } if from == d1 || from == d1 ^ 1 => {
-                AxisMap::Map1 { from, to }
-            }
+            AxisMap::Map1 { from, .. } if from == d1 || from == d1 ^ 1 => self,
             AxisMap::Map1 { from, to } => {
-                let map = match (from, to, d1, d2) {
-                    (from1, to1, from2, to2) if from1 == to1 && from2 == to2 => [0, 1, 2, 3, 4, 5],
-                    (0, 0, 2, 3)
-                    | (0, 0, 3, 2)
-                    | (0, 0, 4, 5)
-                    | (0, 0, 5, 4)
-                    | (1, 1, 2, 3)
-                    | (1, 1, 3, 2)
-                    | (1, 1, 4, 5)
-                    | (1, 1, 5, 4)
-                    | (2, 3, 0, 0)
-                    | (2, 3, 1, 1)
-                    | (2, 3, 3, 2)
-                    | (2, 3, 4, 5)
-                    | (2, 3, 5, 4)
-                    | (3, 2, 0, 0)
-                    | (3, 2, 1, 1)
-                    | (3, 2, 2, 3)
-                    | (3, 2, 4, 5)
-                    | (3, 2, 5, 4)
-                    | (4, 5, 0, 0)
-                    | (4, 5, 1, 1)
-                    | (4, 5, 2, 3)
-                    | (4, 5, 3, 2)
-                    | (4, 5, 5, 4)
-                    | (5, 4, 0, 0)
-                    | (5, 4, 1, 1)
-                    | (5, 4, 2, 3)
-                    | (5, 4, 3, 2)
-                    | (5, 4, 4, 5) => [0, 1, 3, 2, 5, 4],
-                    (0, 0, 2, 4)
-                    | (0, 0, 3, 5)
-                    | (0, 0, 4, 3)
-                    | (0, 0, 5, 2)
-                    | (1, 1, 2, 4)
-                    | (1, 1, 3, 5)
-                    | (1, 1, 4, 3)
-                    | (1, 1, 5, 2)
-                    | (2, 4, 0, 0)
-                    | (2, 4, 1, 1)
-                    | (2, 4, 3, 5)
-                    | (2, 4, 4, 3)
-                    | (2, 4, 5, 2)
-                    | (3, 5, 0, 0)
-                    | (3, 5, 1, 1)
-                    | (3, 5, 2, 4)
-                    | (3, 5, 4, 3)
-                    | (3, 5, 5, 2)
-                    | (4, 3, 0, 0)
-                    | (4, 3, 1, 1)
-                    | (4, 3, 2, 4)
-                    | (4, 3, 3, 5)
-                    | (4, 3, 5, 2)
-                    | (5, 2, 0, 0)
-                    | (5, 2, 1, 1)
-                    | (5, 2, 2, 4)
-                    | (5, 2, 3, 5)
-                    | (5, 2, 4, 3) => [0, 1, 4, 5, 3, 2],
-                    (0, 0, 2, 5)
-                    | (0, 0, 3, 4)
-                    | (0, 0, 4, 2)
-                    | (0, 0, 5, 3)
-                    | (1, 1, 2, 5)
-                    | (1, 1, 3, 4)
-                    | (1, 1, 4, 2)
-                    | (1, 1, 5, 3)
-                    | (2, 5, 0, 0)
-                    | (2, 5, 1, 1)
-                    | (2, 5, 3, 4)
-                    | (2, 5, 4, 2)
-                    | (2, 5, 5, 3)
-                    | (3, 4, 0, 0)
-                    | (3, 4, 1, 1)
-                    | (3, 4, 2, 5)
-                    | (3, 4, 4, 2)
-                    | (3, 4, 5, 3)
-                    | (4, 2, 0, 0)
-                    | (4, 2, 1, 1)
-                    | (4, 2, 2, 5)
-                    | (4, 2, 3, 4)
-                    | (4, 2, 5, 3)
-                    | (5, 3, 0, 0)
-                    | (5, 3, 1, 1)
-                    | (5, 3, 2, 5)
-                    | (5, 3, 3, 4)
-                    | (5, 3, 4, 2) => [0, 1, 5, 4, 2, 3],
-                    (0, 1, 1, 0)
-                    | (0, 1, 2, 2)
-                    | (0, 1, 3, 3)
-                    | (0, 1, 4, 5)
-                    | (0, 1, 5, 4)
-                    | (1, 0, 0, 1)
-                    | (1, 0, 2, 2)
-                    | (1, 0, 3, 3)
-                    | (1, 0, 4, 5)
-                    | (1, 0, 5, 4)
-                    | (2, 2, 0, 1)
-                    | (2, 2, 1, 0)
-                    | (2, 2, 4, 5)
-                    | (2, 2, 5, 4)
-                    | (3, 3, 0, 1)
-                    | (3, 3, 1, 0)
-                    | (3, 3, 4, 5)
-                    | (3, 3, 5, 4)
-                    | (4, 5, 0, 1)
-                    | (4, 5, 1, 0)
-                    | (4, 5, 2, 2)
-                    | (4, 5, 3, 3)
-                    | (5, 4, 0, 1)
-                    | (5, 4, 1, 0)
-                    | (5, 4, 2, 2)
-                    | (5, 4, 3, 3) => [1, 0, 2, 3, 5, 4],
-                    (0, 1, 2, 3)
-                    | (0, 1, 3, 2)
-                    | (0, 1, 4, 4)
-                    | (0, 1, 5, 5)
-                    | (1, 0, 2, 3)
-                    | (1, 0, 3, 2)
-                    | (1, 0, 4, 4)
-                    | (1, 0, 5, 5)
-                    | (2, 3, 0, 1)
-                    | (2, 3, 1, 0)
-                    | (2, 3, 4, 4)
-                    | (2, 3, 5, 5)
-                    | (3, 2, 0, 1)
-                    | (3, 2, 1, 0)
-                    | (3, 2, 4, 4)
-                    | (3, 2, 5, 5)
-                    | (4, 4, 0, 1)
-                    | (4, 4, 1, 0)
-                    | (4, 4, 2, 3)
-                    | (4, 4, 3, 2)
-                    | (5, 5, 0, 1)
-                    | (5, 5, 1, 0)
-                    | (5, 5, 2, 3)
-                    | (5, 5, 3, 2) => [1, 0, 3, 2, 4, 5],
-                    (0, 1, 2, 4)
-                    | (0, 1, 3, 5)
-                    | (0, 1, 4, 2)
-                    | (0, 1, 5, 3)
-                    | (1, 0, 2, 4)
-                    | (1, 0, 3, 5)
-                    | (1, 0, 4, 2)
-                    | (1, 0, 5, 3)
-                    | (2, 4, 0, 1)
-                    | (2, 4, 1, 0)
-                    | (2, 4, 4, 2)
-                    | (2, 4, 5, 3)
-                    | (3, 5, 0, 1)
-                    | (3, 5, 1, 0)
-                    | (3, 5, 4, 2)
-                    | (3, 5, 5, 3)
-                    | (4, 2, 0, 1)
-                    | (4, 2, 1, 0)
-                    | (4, 2, 2, 4)
-                    | (4, 2, 3, 5)
-                    | (5, 3, 0, 1)
-                    | (5, 3, 1, 0)
-                    | (5, 3, 2, 4)
-                    | (5, 3, 3, 5) => [1, 0, 4, 5, 2, 3],
-                    (0, 1, 2, 5)
-                    | (0, 1, 3, 4)
-                    | (0, 1, 4, 3)
-                    | (0, 1, 5, 2)
-                    | (1, 0, 2, 5)
-                    | (1, 0, 3, 4)
-                    | (1, 0, 4, 3)
-                    | (1, 0, 5, 2)
-                    | (2, 5, 0, 1)
-                    | (2, 5, 1, 0)
-                    | (2, 5, 4, 3)
-                    | (2, 5, 5, 2)
-                    | (3, 4, 0, 1)
-                    | (3, 4, 1, 0)
-                    | (3, 4, 4, 3)
-                    | (3, 4, 5, 2)
-                    | (4, 3, 0, 1)
-                    | (4, 3, 1, 0)
-                    | (4, 3, 2, 5)
-                    | (4, 3, 3, 4)
-                    | (5, 2, 0, 1)
-                    | (5, 2, 1, 0)
-                    | (5, 2, 2, 5)
-                    | (5, 2, 3, 4) => [1, 0, 5, 4, 3, 2],
-                    (0, 2, 1, 3)
-                    | (0, 2, 2, 1)
-                    | (0, 2, 3, 0)
-                    | (0, 2, 4, 4)
-                    | (0, 2, 5, 5)
-                    | (1, 3, 0, 2)
-                    | (1, 3, 2, 1)
-                    | (1, 3, 3, 0)
-                    | (1, 3, 4, 4)
-                    | (1, 3, 5, 5)
-                    | (2, 1, 0, 2)
-                    | (2, 1, 1, 3)
-                    | (2, 1, 3, 0)
-                    | (2, 1, 4, 4)
-                    | (2, 1, 5, 5)
-                    | (3, 0, 0, 2)
-                    | (3, 0, 1, 3)
-                    | (3, 0, 2, 1)
-                    | (3, 0, 4, 4)
-                    | (3, 0, 5, 5)
-                    | (4, 4, 0, 2)
-                    | (4, 4, 1, 3)
-                    | (4, 4, 2, 1)
-                    | (4, 4, 3, 0)
-                    | (5, 5, 0, 2)
-                    | (5, 5, 1, 3)
-                    | (5, 5, 2, 1)
-                    | (5, 5, 3, 0) => [2, 3, 1, 0, 4, 5],
-                    (0, 2, 2, 0)
-                    | (0, 2, 3, 1)
-                    | (0, 2, 4, 5)
-                    | (0, 2, 5, 4)
-                    | (1, 3, 2, 0)
-                    | (1, 3, 3, 1)
-                    | (1, 3, 4, 5)
-                    | (1, 3, 5, 4)
-                    | (2, 0, 0, 2)
-                    | (2, 0, 1, 3)
-                    | (2, 0, 4, 5)
-                    | (2, 0, 5, 4)
-                    | (3, 1, 0, 2)
-                    | (3, 1, 1, 3)
-                    | (3, 1, 4, 5)
-                    | (3, 1, 5, 4)
-                    | (4, 5, 0, 2)
-                    | (4, 5, 1, 3)
-                    | (4, 5, 2, 0)
-                    | (4, 5, 3, 1)
-                    | (5, 4, 0, 2)
-                    | (5, 4, 1, 3)
-                    | (5, 4, 2, 0)
-                    | (5, 4, 3, 1) => [2, 3, 0, 1, 5, 4],
-                    (0, 2, 2, 4)
-                    | (0, 2, 3, 5)
-                    | (0, 2, 4, 0)
-                    | (0, 2, 5, 1)
-                    | (1, 3, 2, 4)
-                    | (1, 3, 3, 5)
-                    | (1, 3, 4, 0)
-                    | (1, 3, 5, 1)
-                    | (2, 4, 0, 2)
-                    | (2, 4, 1, 3)
-                    | (2, 4, 4, 0)
-                    | (2, 4, 5, 1)
-                    | (3, 5, 0, 2)
-                    | (3, 5, 1, 3)
-                    | (3, 5, 4, 0)
-                    | (3, 5, 5, 1)
-                    | (4, 0, 0, 2)
-                    | (4, 0, 1, 3)
-                    | (4, 0, 2, 4)
-                    | (4, 0, 3, 5)
-                    | (5, 1, 0, 2)
-                    | (5, 1, 1, 3)
-                    | (5, 1, 2, 4)
-                    | (5, 1, 3, 5) => [2, 3, 4, 5, 0, 1],
-                    (0, 2, 2, 5)
-                    | (0, 2, 3, 4)
-                    | (0, 2, 4, 1)
-                    | (0, 2, 5, 0)
-                    | (1, 3, 2, 5)
-                    | (1, 3, 3, 4)
-                    | (1, 3, 4, 1)
-                    | (1, 3, 5, 0)
-                    | (2, 5, 0, 2)
-                    | (2, 5, 1, 3)
-                    | (2, 5, 4, 1)
-                    | (2, 5, 5, 0)
-                    | (3, 4, 0, 2)
-                    | (3, 4, 1, 3)
-                    | (3, 4, 4, 1)
-                    | (3, 4, 5, 0)
-                    | (4, 1, 0, 2)
-                    | (4, 1, 1, 3)
-                    | (4, 1, 2, 5)
-                    | (4, 1, 3, 4)
-                    | (5, 0, 0, 2)
-                    | (5, 0, 1, 3)
-                    | (5, 0, 2, 5)
-                    | (5, 0, 3, 4) => [2, 3, 5, 4, 1, 0],
-                    (0, 3, 1, 2)
-                    | (0, 3, 2, 0)
-                    | (0, 3, 3, 1)
-                    | (0, 3, 4, 4)
-                    | (0, 3, 5, 5)
-                    | (1, 2, 0, 3)
-                    | (1, 2, 2, 0)
-                    | (1, 2, 3, 1)
-                    | (1, 2, 4, 4)
-                    | (1, 2, 5, 5)
-                    | (2, 0, 0, 3)
-                    | (2, 0, 1, 2)
-                    | (2, 0, 3, 1)
-                    | (2, 0, 4, 4)
-                    | (2, 0, 5, 5)
-                    | (3, 1, 0, 3)
-                    | (3, 1, 1, 2)
-                    | (3, 1, 2, 0)
-                    | (3, 1, 4, 4)
-                    | (3, 1, 5, 5)
-                    | (4, 4, 0, 3)
-                    | (4, 4, 1, 2)
-                    | (4, 4, 2, 0)
-                    | (4, 4, 3, 1)
-                    | (5, 5, 0, 3)
-                    | (5, 5, 1, 2)
-                    | (5, 5, 2, 0)
-                    | (5, 5, 3, 1) => [3, 2, 0, 1, 4, 5],
-                    (0, 3, 2, 1)
-                    | (0, 3, 3, 0)
-                    | (0, 3, 4, 5)
-                    | (0, 3, 5, 4)
-                    | (1, 2, 2, 1)
-                    | (1, 2, 3, 0)
-                    | (1, 2, 4, 5)
-                    | (1, 2, 5, 4)
-                    | (2, 1, 0, 3)
-                    | (2, 1, 1, 2)
-                    | (2, 1, 4, 5)
-                    | (2, 1, 5, 4)
-                    | (3, 0, 0, 3)
-                    | (3, 0, 1, 2)
-                    | (3, 0, 4, 5)
-                    | (3, 0, 5, 4)
-                    | (4, 5, 0, 3)
-                    | (4, 5, 1, 2)
-                    | (4, 5, 2, 1)
-                    | (4, 5, 3, 0)
-                    | (5, 4, 0, 3)
-                    | (5, 4, 1, 2)
-                    | (5, 4, 2, 1)
-                    | (5, 4, 3, 0) => [3, 2, 1, 0, 5, 4],
-                    (0, 3, 2, 4)
-                    | (0, 3, 3, 5)
-                    | (0, 3, 4, 1)
-                    | (0, 3, 5, 0)
-                    | (1, 2, 2, 4)
-                    | (1, 2, 3, 5)
-                    | (1, 2, 4, 1)
-                    | (1, 2, 5, 0)
-                    | (2, 4, 0, 3)
-                    | (2, 4, 1, 2)
-                    | (2, 4, 4, 1)
-                    | (2, 4, 5, 0)
-                    | (3, 5, 0, 3)
-                    | (3, 5, 1, 2)
-                    | (3, 5, 4, 1)
-                    | (3, 5, 5, 0)
-                    | (4, 1, 0, 3)
-                    | (4, 1, 1, 2)
-                    | (4, 1, 2, 4)
-                    | (4, 1, 3, 5)
-                    | (5, 0, 0, 3)
-                    | (5, 0, 1, 2)
-                    | (5, 0, 2, 4)
-                    | (5, 0, 3, 5) => [3, 2, 4, 5, 1, 0],
-                    (0, 3, 2, 5)
-                    | (0, 3, 3, 4)
-                    | (0, 3, 4, 0)
-                    | (0, 3, 5, 1)
-                    | (1, 2, 2, 5)
-                    | (1, 2, 3, 4)
-                    | (1, 2, 4, 0)
-                    | (1, 2, 5, 1)
-                    | (2, 5, 0, 3)
-                    | (2, 5, 1, 2)
-                    | (2, 5, 4, 0)
-                    | (2, 5, 5, 1)
-                    | (3, 4, 0, 3)
-                    | (3, 4, 1, 2)
-                    | (3, 4, 4, 0)
-                    | (3, 4, 5, 1)
-                    | (4, 0, 0, 3)
-                    | (4, 0, 1, 2)
-                    | (4, 0, 2, 5)
-                    | (4, 0, 3, 4)
-                    | (5, 1, 0, 3)
-                    | (5, 1, 1, 2)
-                    | (5, 1, 2, 5)
-                    | (5, 1, 3, 4) => [3, 2, 5, 4, 0, 1],
-                    (0, 4, 1, 5)
-                    | (0, 4, 2, 2)
-                    | (0, 4, 3, 3)
-                    | (0, 4, 4, 1)
-                    | (0, 4, 5, 0)
-                    | (1, 5, 0, 4)
-                    | (1, 5, 2, 2)
-                    | (1, 5, 3, 3)
-                    | (1, 5, 4, 1)
-                    | (1, 5, 5, 0)
-                    | (2, 2, 0, 4)
-                    | (2, 2, 1, 5)
-                    | (2, 2, 4, 1)
-                    | (2, 2, 5, 0)
-                    | (3, 3, 0, 4)
-                    | (3, 3, 1, 5)
-                    | (3, 3, 4, 1)
-                    | (3, 3, 5, 0)
-                    | (4, 1, 0, 4)
-                    | (4, 1, 1, 5)
-                    | (4, 1, 2, 2)
-                    | (4, 1, 3, 3)
-                    | (4, 1, 5, 0)
-                    | (5, 0, 0, 4)
-                    | (5, 0, 1, 5)
-                    | (5, 0, 2, 2)
-                    | (5, 0, 3, 3)
-                    | (5, 0, 4, 1) => [4, 5, 2, 3, 1, 0],
-                    (0, 4, 2, 0)
-                    | (0, 4, 3, 1)
-                    | (0, 4, 4, 2)
-                    | (0, 4, 5, 3)
-                    | (1, 5, 2, 0)
-                    | (1, 5, 3, 1)
-                    | (1, 5, 4, 2)
-                    | (1, 5, 5, 3)
-                    | (2, 0, 0, 4)
-                    | (2, 0, 1, 5)
-                    | (2, 0, 4, 2)
-                    | (2, 0, 5, 3)
-                    | (3, 1, 0, 4)
-                    | (3, 1, 1, 5)
-                    | (3, 1, 4, 2)
-                    | (3, 1, 5, 3)
-                    | (4, 2, 0, 4)
-                    | (4, 2, 1, 5)
-                    | (4, 2, 2, 0)
-                    | (4, 2, 3, 1)
-                    | (5, 3, 0, 4)
-                    | (5, 3, 1, 5)
-                    | (5, 3, 2, 0)
-                    | (5, 3, 3, 1) => [4, 5, 0, 1, 2, 3],
-                    (0, 4, 2, 1)
-                    | (0, 4, 3, 0)
-                    | (0, 4, 4, 3)
-                    | (0, 4, 5, 2)
-                    | (1, 5, 2, 1)
-                    | (1, 5, 3, 0)
-                    | (1, 5, 4, 3)
-                    | (1, 5, 5, 2)
-                    | (2, 1, 0, 4)
-                    | (2, 1, 1, 5)
-                    | (2, 1, 4, 3)
-                    | (2, 1, 5, 2)
-                    | (3, 0, 0, 4)
-                    | (3, 0, 1, 5)
-                    | (3, 0, 4, 3)
-                    | (3, 0, 5, 2)
-                    | (4, 3, 0, 4)
-                    | (4, 3, 1, 5)
-                    | (4, 3, 2, 1)
-                    | (4, 3, 3, 0)
-                    | (5, 2, 0, 4)
-                    | (5, 2, 1, 5)
-                    | (5, 2, 2, 1)
-                    | (5, 2, 3, 0) => [4, 5, 1, 0, 3, 2],
-                    (0, 4, 2, 3)
-                    | (0, 4, 3, 2)
-                    | (0, 4, 4, 0)
-                    | (0, 4, 5, 1)
-                    | (1, 5, 2, 3)
-                    | (1, 5, 3, 2)
-                    | (1, 5, 4, 0)
-                    | (1, 5, 5, 1)
-                    | (2, 3, 0, 4)
-                    | (2, 3, 1, 5)
-                    | (2, 3, 4, 0)
-                    | (2, 3, 5, 1)
-                    | (3, 2, 0, 4)
-                    | (3, 2, 1, 5)
-                    | (3, 2, 4, 0)
-                    | (3, 2, 5, 1)
-                    | (4, 0, 0, 4)
-                    | (4, 0, 1, 5)
-                    | (4, 0, 2, 3)
-                    | (4, 0, 3, 2)
-                    | (5, 1, 0, 4)
-                    | (5, 1, 1, 5)
-                    | (5, 1, 2, 3)
-                    | (5, 1, 3, 2) => [4, 5, 3, 2, 0, 1],
-                    (0, 5, 1, 4)
-                    | (0, 5, 2, 2)
-                    | (0, 5, 3, 3)
-                    | (0, 5, 4, 0)
-                    | (0, 5, 5, 1)
-                    | (1, 4, 0, 5)
-                    | (1, 4, 2, 2)
-                    | (1, 4, 3, 3)
-                    | (1, 4, 4, 0)
-                    | (1, 4, 5, 1)
-                    | (2, 2, 0, 5)
-                    | (2, 2, 1, 4)
-                    | (2, 2, 4, 0)
-                    | (2, 2, 5, 1)
-                    | (3, 3, 0, 5)
-                    | (3, 3, 1, 4)
-                    | (3, 3, 4, 0)
-                    | (3, 3, 5, 1)
-                    | (4, 0, 0, 5)
-                    | (4, 0, 1, 4)
-                    | (4, 0, 2, 2)
-                    | (4, 0, 3, 3)
-                    | (4, 0, 5, 1)
-                    | (5, 1, 0, 5)
-                    | (5, 1, 1, 4)
-                    | (5, 1, 2, 2)
-                    | (5, 1, 3, 3)
-                    | (5, 1, 4, 0) => [5, 4, 2, 3, 0, 1],
-                    (0, 5, 2, 0)
-                    | (0, 5, 3, 1)
-                    | (0, 5, 4, 3)
-                    | (0, 5, 5, 2)
-                    | (1, 4, 2, 0)
-                    | (1, 4, 3, 1)
-                    | (1, 4, 4, 3)
-                    | (1, 4, 5, 2)
-                    | (2, 0, 0, 5)
-                    | (2, 0, 1, 4)
-                    | (2, 0, 4, 3)
-                    | (2, 0, 5, 2)
-                    | (3, 1, 0, 5)
-                    | (3, 1, 1, 4)
-                    | (3, 1, 4, 3)
-                    | (3, 1, 5, 2)
-                    | (4, 3, 0, 5)
-                    | (4, 3, 1, 4)
-                    | (4, 3, 2, 0)
-                    | (4, 3, 3, 1)
-                    | (5, 2, 0, 5)
-                    | (5, 2, 1, 4)
-                    | (5, 2, 2, 0)
-                    | (5, 2, 3, 1) => [5, 4, 0, 1, 3, 2],
-                    (0, 5, 2, 1)
-                    | (0, 5, 3, 0)
-                    | (0, 5, 4, 2)
-                    | (0, 5, 5, 3)
-                    | (1, 4, 2, 1)
-                    | (1, 4, 3, 0)
-                    | (1, 4, 4, 2)
-                    | (1, 4, 5, 3)
-                    | (2, 1, 0, 5)
-                    | (2, 1, 1, 4)
-                    | (2, 1, 4, 2)
-                    | (2, 1, 5, 3)
-                    | (3, 0, 0, 5)
-                    | (3, 0, 1, 4)
-                    | (3, 0, 4, 2)
-                    | (3, 0, 5, 3)
-                    | (4, 2, 0, 5)
-                    | (4, 2, 1, 4)
-                    | (4, 2, 2, 1)
-                    | (4, 2, 3, 0)
-                    | (5, 3, 0, 5)
-                    | (5, 3, 1, 4)
-                    | (5, 3, 2, 1)
-                    | (5, 3, 3, 0) => [5, 4, 1, 0, 2, 3],
-                    (0, 5, 2, 3)
-                    | (0, 5, 3, 2)
-                    | (0, 5, 4, 1)
-                    | (0, 5, 5, 0)
-                    | (1, 4, 2, 3)
-                    | (1, 4, 3, 2)
-                    | (1, 4, 4, 1)
-                    | (1, 4, 5, 0)
-                    | (2, 3, 0, 5)
-                    | (2, 3, 1, 4)
-                    | (2, 3, 4, 1)
-                    | (2, 3, 5, 0)
-                    | (3, 2, 0, 5)
-                    | (3, 2, 1, 4)
-                    | (3, 2, 4, 1)
-                    | (3, 2, 5, 0)
-                    | (4, 1, 0, 5)
-                    | (4, 1, 1, 4)
-                    | (4, 1, 2, 3)
-                    | (4, 1, 3, 2)
-                    | (5, 0, 0, 5)
-                    | (5, 0, 1, 4)
-                    | (5, 0, 2, 3)
-                    | (5, 0, 3, 2) => [5, 4, 3, 2, 1, 0],
-                    x => panic!("{:?}", x),
-                };
-                AxisMap::Map2 { map }
+                let rot = Rotation::from_pairs(from, to, d1, d2)
+                    .unwrap_or_else(|| panic!("{:?}", (from, to, d1, d2)));
+                AxisMap::Map2 { rot }
             }
             m => m,
         }
@@ -684,40 +130,23 @@ impl AxisMap {
             AxisMap::None => directions.into(),
             AxisMap::Map1 { from, to } => {
                 if from == direction || from == direction ^ 1 {
-                    return smallvec![to];
+                    return smallvec![to ^ (from ^ direction)];
                 }
-                let map = match (from, to) {
-                    (from, to) if from == to => [0, 1, 2, 3, 4, 5],
-                    (0, 1) | (1, 0) => [1, 0, 2, 3, 5, 4],
-                    (0, 2) | (2, 0) => [2, 3, 0, 1, 5, 4],
-                    (0, 3) | (3, 0) => [3, 2, 1, 0, 5, 4],
-                    (0, 4) | (4, 0) => [4, 5, 3, 2, 0, 1],
-                    (0, 5) | (5, 0) => [5, 4, 3, 2, 1, 0],
-                    (1, 2) | (2, 1) => [3, 2, 1, 0, 5, 4],
-                    (1, 3) | (3, 1) => [2, 3, 0, 1, 5, 4],
-                    (1, 4) | (4, 1) => [5, 4, 3, 2, 1, 0],
-                    (1, 5) | (5, 1) => [4, 5, 3, 2, 0, 1],
-                    (2, 3) | (3, 2) => [0, 1, 3, 2, 5, 4],
-                    (2, 4) | (4, 2) => [1, 0, 4, 5, 2, 3],
-                    (2, 5) | (5, 2) => [1, 0, 5, 4, 3, 2],
-                    (3, 4) | (4, 3) => [1, 0, 5, 4, 3, 2],
-                    (3, 5) | (5, 3) => [1, 0, 4, 5, 2, 3],
-                    (4, 5) | (5, 4) => [0, 1, 3, 2, 5, 4],
-                    _ => unreachable!(),
-                };
+                // 軸まわりの回転は自由なので `to` に垂直な 4 方向が候補
+                let rot = Rotation::half_turn(from, to);
                 directions
                     .iter()
                     .filter_map(|&d| {
                         if d == from || d == from ^ 1 {
                             None
                         } else {
-                            Some(map[d as usize])
+                            Some(rot.apply_dir(d))
                         }
                     })
                     .collect()
             }
-            AxisMap::Map2 { map } => {
-                smallvec![map[direction as usize]]
+            AxisMap::Map2 { rot } => {
+                smallvec![rot.apply_dir(direction)]
             }
         }
     }
@@ -737,6 +166,7 @@ impl<T> Grid3<T> {
     #[inline(always)]
     fn at(&self, p: Point) -> usize {
         let Point(x, y, z) = p;
+        debug_assert!(x < self.d && y < self.d && z < self.d);
         let d = self.d as usize;
         let x = x as usize;
         let y = y as usize;
@@ -746,26 +176,30 @@ impl<T> Grid3<T> {
 }
 
 impl<T> GridFront<T> {
+    /// `data[x * d + z]` is the value at `(x, z)`.
     pub fn from_vec(d: u8, data: Vec<T>) -> GridFront<T> {
+        check_len(d, 2, data.len()).unwrap();
         GridFront { d, data }
     }
 
     #[inline(always)]
     fn at(&self, p: Point) -> usize {
         let Point(x, _, z) = p;
-        (x * self.d + z) as usize
+        index2(self.d, x, z)
     }
 }
 
 impl<T> GridRight<T> {
+    /// `data[z * d + y]` is the value at `(y, z)`.
     pub fn from_vec(d: u8, data: Vec<T>) -> GridRight<T> {
+        check_len(d, 2, data.len()).unwrap();
         GridRight { d, data }
     }
 
     #[inline(always)]
     fn at(&self, p: Point) -> usize {
         let Point(_, y, z) = p;
-        (z * self.d + y) as usize
+        index2(self.d, z, y)
     }
 
     pub fn row(&self, z: usize) -> &[T] {
@@ -774,6 +208,66 @@ impl<T> GridRight<T> {
     }
 }
 
+/// `usize` で計算する。u8 のままだと D >= 16 で溢れる
+#[inline(always)]
+fn index2(d: u8, a: u8, b: u8) -> usize {
+    debug_assert!(a < d && b < d);
+    a as usize * d as usize + b as usize
+}
+
+/// Checks that a grid of `d` with `dim` axes has `len` values.
+fn check_len(d: u8, dim: u32, len: usize) -> Result<(), String> {
+    let want = (d as usize).pow(dim);
+    if len == want {
+        Ok(())
+    } else {
+        Err(format!(
+            "a grid of D={} needs {} values, got {}",
+            d, want, len
+        ))
+    }
+}
+
+/// Deserialized grids go through `check_len` since indexing is unchecked.
+#[derive(Deserialize)]
+struct RawGrid<T> {
+    d: u8,
+    data: Vec<T>,
+}
+
+impl<T> TryFrom<RawGrid<T>> for Grid3<T> {
+    type Error = String;
+    fn try_from(raw: RawGrid<T>) -> Result<Self, String> {
+        check_len(raw.d, 3, raw.data.len())?;
+        Ok(Grid3 {
+            d: raw.d,
+            data: raw.data,
+        })
+    }
+}
+
+impl<T> TryFrom<RawGrid<T>> for GridFront<T> {
+    type Error = String;
+    fn try_from(raw: RawGrid<T>) -> Result<Self, String> {
+        check_len(raw.d, 2, raw.data.len())?;
+        Ok(GridFront {
+            d: raw.d,
+            data: raw.data,
+        })
+    }
+}
+
+impl<T> TryFrom<RawGrid<T>> for GridRight<T> {
+    type Error = String;
+    fn try_from(raw: RawGrid<T>) -> Result<Self, String> {
+        check_len(raw.d, 2, raw.data.len())?;
+        Ok(GridRight {
+            d: raw.d,
+            data: raw.data,
+        })
+    }
+}
+
 impl<T> Index<Point> for Grid3<T> {
     type Output = T;
     fn index(&self, p: Point) -> &T {
@@ -819,14 +313,14 @@ impl<T> IndexMut<Point> for GridRight<T> {
 impl<T> Index<(u8, u8)> for GridFront<T> {
     type Output = T;
     fn index(&self, p: (u8, u8)) -> &T {
-        let i = (p.0 * self.d + p.1) as usize;
+        let i = index2(self.d, p.0, p.1);
         unsafe { self.data.get_unchecked(i) }
     }
 }
 
 impl<T> IndexMut<(u8, u8)> for GridFront<T> {
     fn index_mut(&mut self, p: (u8, u8)) -> &mut T {
-        let i = (p.0 * self.d + p.1) as usize;
+        let i = index2(self.d, p.0, p.1);
         unsafe { self.data.get_unchecked_mut(i) }
     }
 }
@@ -835,7 +329,7 @@ impl<T> Index<(u8, u8)> for GridRight<T> {
     type Output = T;
     fn index(&self, p: (u8, u8)) -> &T {
         let (y, z) = p;
-        let i = (z * self.d + y) as usize;
+        let i = index2(self.d, z, y);
         unsafe { self.data.get_unchecked(i) }
     }
 }
@@ -843,7 +337,7 @@ impl<T> Index<(u8, u8)> for GridRight<T> {
 impl<T> IndexMut<(u8, u8)> for GridRight<T> {
     fn index_mut(&mut self, p: (u8, u8)) -> &mut T {
         let (y, z) = p;
-        let i = (z * self.d + y) as usize;
+        let i = index2(self.d, z, y);
         unsafe { self.data.get_unchecked_mut(i) }
     }
 }
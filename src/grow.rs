@@ -0,0 +1,84 @@
+use crate::{GridSystem, Point, Rotation, SolveInput, SolveResult};
+
+/// A congruence `p -> rot(p) + shift` carrying one copy of a shared block onto another.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Placement {
+    pub rot: Rotation,
+    pub shift: [i32; 3],
+}
+
+fn coords(p: Point) -> [i32; 3] {
+    let (x, y, z) = p.xyz();
+    [x as i32, y as i32, z as i32]
+}
+
+fn min_corner(v: &[[i32; 3]]) -> [i32; 3] {
+    let mut min = [i32::MAX; 3];
+    for c in v.iter() {
+        for k in 0..3 {
+            min[k] = min[k].min(c[k]);
+        }
+    }
+    min
+}
+
+impl Placement {
+    /// How to carry the cells `from` onto the cells `to`, if the two are congruent.
+    pub fn find(from: &[Point], to: &[Point]) -> Option<Placement> {
+        if from.len() != to.len() || from.is_empty() {
+            return None;
+        }
+        let mut target: Vec<_> = to.iter().map(|&p| coords(p)).collect();
+        target.sort_unstable();
+        let target_min = min_corner(&target);
+        for rot in Rotation::all() {
+            let mut rotated: Vec<_> = from.iter().map(|&p| rot.apply(coords(p))).collect();
+            let min = min_corner(&rotated);
+            let shift = [
+                target_min[0] - min[0],
+                target_min[1] - min[1],
+                target_min[2] - min[2],
+            ];
+            for c in rotated.iter_mut() {
+                for k in 0..3 {
+                    c[k] += shift[k];
+                }
+            }
+            rotated.sort_unstable();
+            if rotated == target {
+                return Some(Placement { rot, shift });
+            }
+        }
+        None
+    }
+
+    /// The image of `p`, if it is inside the `d`-cube.
+    pub fn apply(self, p: Point, d: u8) -> Option<Point> {
+        let v = self.rot.apply(coords(p));
+        let mut q = [0u8; 3];
+        for k in 0..3 {
+            let c = v[k] + self.shift[k];
+            if c < 0 || c >= d as i32 {
+                return None;
+            }
+            q[k] = c as u8;
+        }
+        Some(Point::new(q[0], q[1], q[2]))
+    }
+}
+
+impl SolveResult {
+    /// Grows every shared block as far as it goes into free cells and leaf cells of unshared
+    /// blocks, then sets `score` to that of the answer.
+    pub fn polish(&mut self, input: &SolveInput) {
+        if self.grids.is_empty() {
+            return;
+        }
+        let mut grid = GridSystem::from_result(input.d(), input, self);
+        for i in 0..grid.block().shared.len() {
+            grid.grow_shared(i, true);
+        }
+        self.grids = grid.grids().iter().map(|g| g.data().to_vec()).collect();
+        self.score = self.exact_score();
+    }
+}
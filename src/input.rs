@@ -0,0 +1,227 @@
+use crate::{Budget, McParams, Silhouette, SolveInput};
+use std::{
+    fmt, io,
+    time::{Duration, Instant},
+};
+
+pub const MIN_D: usize = 5;
+pub const MAX_D: usize = 64;
+
+/// front1, right1, front2, right2, front3, ...
+fn silhouette_name(silhouette: usize) -> String {
+    let side = if silhouette.is_multiple_of(2) {
+        "front"
+    } else {
+        "right"
+    };
+    format!("{}{}", side, silhouette / 2 + 1)
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    Io(io::Error),
+    UnexpectedEof,
+    InvalidD(String),
+    DOutOfRange(usize),
+    /// `silhouette` は front1, right1, front2, right2, ... の順の番号
+    EmptyRow {
+        silhouette: usize,
+        row: usize,
+    },
+    /// 行数が D でない (`tools::Input` から作るときだけ起きる)
+    RowCount {
+        silhouette: usize,
+        rows: usize,
+        d: usize,
+    },
+    RowLength {
+        silhouette: usize,
+        row: usize,
+        len: usize,
+    },
+    InvalidChar {
+        silhouette: usize,
+        row: usize,
+        col: usize,
+        value: String,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io(e) => write!(f, "{}", e),
+            InputError::UnexpectedEof => write!(f, "unexpected EOF"),
+            InputError::InvalidD(s) => write!(f, "invalid D: {:?}", s),
+            InputError::DOutOfRange(d) => {
+                write!(f, "D={} is out of range {}..={}", d, MIN_D, MAX_D)
+            }
+            InputError::EmptyRow { silhouette, row } => {
+                write!(f, "{} row {} is empty", silhouette_name(*silhouette), row)
+            }
+            InputError::RowCount {
+                silhouette,
+                rows,
+                d,
+            } => write!(
+                f,
+                "{} has {} rows, expected D={}",
+                silhouette_name(*silhouette),
+                rows,
+                d
+            ),
+            InputError::RowLength {
+                silhouette,
+                row,
+                len,
+            } => write!(
+                f,
+                "{} row {} has length {}, expected D",
+                silhouette_name(*silhouette),
+                row,
+                len
+            ),
+            InputError::InvalidChar {
+                silhouette,
+                row,
+                col,
+                value,
+            } => write!(
+                f,
+                "{} row {} column {} is {:?}, expected 0 or 1",
+                silhouette_name(*silhouette),
+                row,
+                col,
+                value
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl From<io::Error> for InputError {
+    fn from(e: io::Error) -> Self {
+        InputError::Io(e)
+    }
+}
+
+fn check_d(d: usize) -> Result<(), InputError> {
+    if (MIN_D..=MAX_D).contains(&d) {
+        Ok(())
+    } else {
+        Err(InputError::DOutOfRange(d))
+    }
+}
+
+fn check_row(d: usize, silhouette: usize, row: usize, v: &[u8]) -> Result<(), InputError> {
+    if v.is_empty() {
+        return Err(InputError::EmptyRow { silhouette, row });
+    }
+    if v.len() != d {
+        return Err(InputError::RowLength {
+            silhouette,
+            row,
+            len: v.len(),
+        });
+    }
+    if let Some(col) = v.iter().position(|&c| c != b'0' && c != b'1') {
+        return Err(InputError::InvalidChar {
+            silhouette,
+            row,
+            col,
+            value: (v[col] as char).to_string(),
+        });
+    }
+    Ok(())
+}
+
+impl SolveInput {
+    /// `start` is now, `budget` is the contest time limit and `params` is `McParams::opt(d)`;
+    /// callers override them as needed.
+    fn from_silhouettes(s: Vec<Vec<Vec<u8>>>) -> SolveInput {
+        let d = s[0].len() as u8;
+        let mut s = s.into_iter();
+        let mut objects = vec![];
+        while let (Some(front), Some(right)) = (s.next(), s.next()) {
+            objects.push(Silhouette { front, right });
+        }
+        SolveInput {
+            start: Instant::now(),
+            budget: Budget::time(Duration::from_millis(5800)),
+            objects,
+            params: McParams::opt(d),
+        }
+    }
+
+    pub fn from_tools_input(input: &tools::Input) -> Result<SolveInput, InputError> {
+        check_d(input.d)?;
+        if input.f.len() < 2 || input.f.len() != input.r.len() {
+            return Err(InputError::UnexpectedEof);
+        }
+        let mut silhouettes = Vec::with_capacity(2 * input.f.len());
+        let shadows = input.f.iter().zip(input.r.iter()).flat_map(|(f, r)| [f, r]);
+        for (silhouette, shadow) in shadows.enumerate() {
+            if shadow.len() != input.d {
+                return Err(InputError::RowCount {
+                    silhouette,
+                    rows: shadow.len(),
+                    d: input.d,
+                });
+            }
+            let mut rows = Vec::with_capacity(input.d);
+            for (row, v) in shadow.iter().enumerate() {
+                if let Some(col) = v.iter().position(|&c| c != 0 && c != 1) {
+                    return Err(InputError::InvalidChar {
+                        silhouette,
+                        row,
+                        col,
+                        value: v[col].to_string(),
+                    });
+                }
+                let v: Vec<u8> = v.iter().map(|&c| b'0' + c as u8).collect();
+                check_row(input.d, silhouette, row, &v)?;
+                rows.push(v);
+            }
+            silhouettes.push(rows);
+        }
+        Ok(SolveInput::from_silhouettes(silhouettes))
+    }
+
+    /// Parses the contest input format. More than two objects may follow, each as its front and
+    /// right silhouettes.
+    pub fn parse(s: &str) -> Result<SolveInput, InputError> {
+        let mut lines = s.lines().map(|line| line.trim());
+        let d = lines
+            .by_ref()
+            .find(|line| !line.is_empty())
+            .ok_or(InputError::UnexpectedEof)?;
+        let d: usize = d.parse().map_err(|_| InputError::InvalidD(d.to_owned()))?;
+        check_d(d)?;
+        let mut silhouettes = Vec::with_capacity(4);
+        // 残りが空行だけになるまで物体を読む
+        while silhouettes.len() < 4 || lines.clone().any(|line| !line.is_empty()) {
+            for _ in 0..2 {
+                let silhouette = silhouettes.len();
+                let mut rows = Vec::with_capacity(d);
+                for row in 0..d {
+                    let v = lines.next().ok_or(InputError::UnexpectedEof)?.as_bytes();
+                    check_row(d, silhouette, row, v)?;
+                    rows.push(v.to_vec());
+                }
+                silhouettes.push(rows);
+            }
+        }
+        Ok(SolveInput::from_silhouettes(silhouettes))
+    }
+
+    pub fn from_reader<R: io::Read>(mut r: R) -> Result<SolveInput, InputError> {
+        let mut s = String::new();
+        r.read_to_string(&mut s)?;
+        SolveInput::parse(&s)
+    }
+
+    pub fn d(&self) -> u8 {
+        self.objects[0].front.len() as u8
+    }
+}
@@ -1,10 +1,31 @@
+mod answer;
+mod bandit;
 mod block_set;
-// pub mod brute_force;
+mod bound;
+mod brute_force;
+mod budget;
+mod eval;
 mod grid;
+mod grow;
+mod input;
 mod mc;
+mod moves;
+mod observer;
+mod param_table;
+mod prune;
 mod solver;
 
+pub use bandit::*;
 pub use block_set::*;
+pub use bound::*;
+pub use brute_force::*;
+pub use budget::*;
+pub use eval::*;
 pub use grid::*;
+pub use grow::*;
+pub use input::*;
 pub use mc::*;
+pub use moves::*;
+pub use observer::*;
+pub use param_table::*;
 pub use solver::*;
@@ -1,74 +1,74 @@
-#[derive(Debug, Copy, Clone, Default)]
+use crate::{MoveWeights, ParamTable};
+use serde::{Deserialize, Serialize};
+
+/// How `mc_run` decides whether to keep a refilled state.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+pub enum Acceptance {
+    /// Keep only strict improvements.
+    #[default]
+    HillClimb,
+    /// Also keep worse states with probability `exp(-delta / temperature)`.
+    Annealing,
+}
+
+/// How the temperature moves from `start_temp` to `end_temp` over a run.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+pub enum Schedule {
+    Linear,
+    /// Geometric interpolation, both temperatures must be positive.
+    #[default]
+    Exponential,
+}
+
+/// How `mc_run` picks the move and the erase parameters of each step.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+pub enum Selection {
+    /// Draw a move by `McParams::moves`, always with `erase_small_th` and `erase_shared_p`.
+    #[default]
+    Weighted,
+    /// Discounted UCB over the recent improvement rate of each move with a positive weight,
+    /// combined with scaled `erase_small_th` and `erase_shared_p`.
+    Bandit,
+}
+
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct McParams {
     pub mc_run: u64,
     pub erase_small_th: usize,
     pub erase_shared_p: f64,
+    /// `MoveSet::from_params` の重み
+    pub moves: MoveWeights,
+    pub selection: Selection,
+    pub acceptance: Acceptance,
+    pub start_temp: f64,
+    pub end_temp: f64,
+    pub schedule: Schedule,
 }
 
 impl McParams {
+    /// Tuned parameters for `d` from the embedded `ParamTable`.
     pub fn opt(d: u8) -> McParams {
-        match d {
-            // -0.8665073454427983
-            5 => McParams {
-                erase_shared_p: 0.7641974640494824,
-                erase_small_th: 5,
-                mc_run: 45,
-            },
-            // -1.0465880873030173
-            6 => McParams {
-                erase_shared_p: 0.5271543071699281,
-                erase_small_th: 8,
-                mc_run: 100,
-            },
-            // -1.062290396485194
-            7 => McParams {
-                erase_shared_p: 0.3526343942727514,
-                erase_small_th: 11,
-                mc_run: 64,
-            },
-            // -1.0776700405881754
-            8 => McParams {
-                erase_shared_p: 0.6445607027301276,
-                erase_small_th: 14,
-                mc_run: 78,
-            },
-            // -1.114248273423888
-            9 => McParams {
-                erase_shared_p: 0.5778111909809597,
-                erase_small_th: 21,
-                mc_run: 30,
-            },
-            // -1.2071752742275605
-            10 => McParams {
-                erase_shared_p: 0.5016439719628357,
-                erase_small_th: 24,
-                mc_run: 24,
-            },
-            // -1.047992358709207
-            11 => McParams {
-                erase_shared_p: 0.5337580963073999,
-                erase_small_th: 29,
-                mc_run: 41,
-            },
-            // -1.4402375267601855
-            12 => McParams {
-                erase_shared_p: 0.5627626458269703,
-                erase_small_th: 39,
-                mc_run: 47,
-            },
-            // -1.429345287977688
-            13 => McParams {
-                erase_shared_p: 0.653866755894219,
-                erase_small_th: 48,
-                mc_run: 24,
-            },
-            // -1.6196117306757933
-            14 => McParams {
-                erase_shared_p: 0.7227385983082774,
-                erase_small_th: 60,
-                mc_run: 17,
-            },
-            _ => unreachable!(),
+        ParamTable::embedded().get(d)
+    }
+
+    pub fn annealing(self, start_temp: f64, end_temp: f64) -> McParams {
+        McParams {
+            acceptance: Acceptance::Annealing,
+            start_temp,
+            end_temp,
+            ..self
+        }
+    }
+
+    /// Temperature at `progress` in `[0, 1]` of a run.
+    pub fn temperature(&self, progress: f64) -> f64 {
+        let progress = progress.clamp(0.0, 1.0);
+        match self.schedule {
+            Schedule::Linear => self.start_temp + (self.end_temp - self.start_temp) * progress,
+            Schedule::Exponential => {
+                self.start_temp * (self.end_temp / self.start_temp).powf(progress)
+            }
         }
     }
 }
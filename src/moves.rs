@@ -0,0 +1,284 @@
+use crate::{GridSystem, McParams};
+use rand::Rng;
+use rand_pcg::Mcg128Xsl64;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// One neighbourhood of `mc_run`.
+///
+/// A move takes blocks out of (or changes) the committed state; `mc_run` then refills the
+/// silhouettes with `fill_all` and keeps or rolls back the result. Every change has to go
+/// through the journals of `GridSystem`, so that `rollback` undoes it.
+pub trait Move {
+    /// Name in `MoveStats`.
+    fn name(&self) -> &'static str;
+
+    fn apply(&mut self, rng: &mut Mcg128Xsl64, grid: &mut GridSystem, params: &McParams);
+
+    /// Told whether the step this move made was kept and whether it was strictly better.
+    fn feedback(&mut self, _accepted: bool, _improved: bool) {}
+
+    /// State learned by `feedback`, kept in `McState` so that a resumed run continues with it.
+    fn save(&self) -> Vec<f64> {
+        Vec::new()
+    }
+
+    /// Takes back what `save` returned. A checkpoint may come from another version of the
+    /// move, so a `saved` of the wrong shape should leave the state as it is.
+    fn restore(&mut self, _saved: &[f64]) {}
+}
+
+/// Erases one random shared block with probability `erase_shared_p`; half blocks and small
+/// shared blocks are already erased at the start of each step.
+pub struct EraseRefill;
+
+impl Move for EraseRefill {
+    fn name(&self) -> &'static str {
+        "erase_refill"
+    }
+
+    fn apply(&mut self, rng: &mut Mcg128Xsl64, grid: &mut GridSystem, params: &McParams) {
+        grid.erase_shared(rng, params.erase_shared_p);
+    }
+}
+
+/// Grows one random shared block greedily into free cells.
+pub struct GrowShared;
+
+impl Move for GrowShared {
+    fn name(&self) -> &'static str {
+        "grow_shared"
+    }
+
+    fn apply(&mut self, rng: &mut Mcg128Xsl64, grid: &mut GridSystem, _params: &McParams) {
+        let n = grid.block().shared.len();
+        if n > 0 {
+            grid.grow_shared(rng.gen_range(0, n), false);
+        }
+    }
+}
+
+/// Erases every shared block with a cube in a random region of one object, from all objects,
+/// so that `fill_all` rebuilds the whole region.
+///
+/// The region is a box, a ball or a range of z. Its size follows the acceptance rate: it
+/// grows a little after each kept step and shrinks a little after each rejected one, settling
+/// where about one step in ten is kept.
+#[derive(Debug, Clone)]
+pub struct RegionErase {
+    /// 箱と球の半径、z 範囲は幅の半分 (セル単位)
+    pub radius: f64,
+    max_radius: f64,
+}
+
+impl RegionErase {
+    const GROW: f64 = 1.1;
+    const SHRINK: f64 = 0.99;
+
+    pub fn new() -> RegionErase {
+        RegionErase {
+            radius: 1.0,
+            max_radius: 1.0,
+        }
+    }
+}
+
+impl Default for RegionErase {
+    fn default() -> Self {
+        RegionErase::new()
+    }
+}
+
+impl Move for RegionErase {
+    fn name(&self) -> &'static str {
+        "region_erase"
+    }
+
+    fn apply(&mut self, rng: &mut Mcg128Xsl64, grid: &mut GridSystem, _params: &McParams) {
+        let object = &grid.grids()[rng.gen_range(0, grid.grids().len())];
+        let d = object.d() as i32;
+        self.max_radius = d as f64;
+        let r = self.radius.ceil() as i32;
+        let c = [
+            rng.gen_range(0, d),
+            rng.gen_range(0, d),
+            rng.gen_range(0, d),
+        ];
+        let shape = rng.gen_range(0, 3);
+        let range = |k: usize| {
+            if shape == 2 && k < 2 {
+                0..d
+            } else {
+                (c[k] - r).max(0)..(c[k] + r + 1).min(d)
+            }
+        };
+        let mut ids = BTreeSet::new();
+        for x in range(0) {
+            for y in range(1) {
+                for z in range(2) {
+                    let (dx, dy, dz) = (x - c[0], y - c[1], z - c[2]);
+                    if shape == 1 && ((dx * dx + dy * dy + dz * dz) as f64) > self.radius.powi(2) {
+                        continue;
+                    }
+                    let id = object.data()[((x * d + y) * d + z) as usize];
+                    if id != 0 && id != !0 {
+                        ids.insert(id);
+                    }
+                }
+            }
+        }
+        // 片側ブロックはステップの最初に消えているので、ここで消えるのは共有ブロックだけ
+        for id in ids {
+            grid.erase_shared_id(id);
+        }
+    }
+
+    fn feedback(&mut self, accepted: bool, _improved: bool) {
+        let factor = if accepted { Self::GROW } else { Self::SHRINK };
+        self.radius = (self.radius * factor).clamp(1.0, self.max_radius.max(1.0));
+    }
+
+    fn save(&self) -> Vec<f64> {
+        vec![self.radius, self.max_radius]
+    }
+
+    fn restore(&mut self, saved: &[f64]) {
+        // 形が違えば今の大きさのまま
+        if let [radius, max_radius] = *saved {
+            self.radius = radius;
+            self.max_radius = max_radius;
+        }
+    }
+}
+
+/// Relative weights of the built-in moves in `MoveSet::from_params`.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MoveWeights {
+    pub erase_refill: f64,
+    pub grow_shared: f64,
+    pub region_erase: f64,
+}
+
+impl Default for MoveWeights {
+    fn default() -> Self {
+        MoveWeights {
+            erase_refill: 1.0,
+            grow_shared: 0.0,
+            region_erase: 0.0,
+        }
+    }
+}
+
+/// How often one move was tried and kept during one `mc_run`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveStats {
+    pub name: String,
+    pub tried: u64,
+    /// 焼きなましで悪くなっても受理したものを含む
+    pub accepted: u64,
+    /// 今の点数より真に良くなった回数
+    pub improved: u64,
+}
+
+/// The moves `mc_run` draws from, each with its weight.
+#[derive(Default)]
+pub struct MoveSet {
+    moves: Vec<(Box<dyn Move>, f64)>,
+}
+
+impl MoveSet {
+    pub fn new() -> MoveSet {
+        MoveSet::default()
+    }
+
+    pub fn with(mut self, m: impl Move + 'static, weight: f64) -> MoveSet {
+        assert!(weight >= 0.0, "negative weight for {}", m.name());
+        self.moves.push((Box::new(m), weight));
+        self
+    }
+
+    /// The built-in moves with `params.moves` as weights.
+    pub fn from_params(params: &McParams) -> MoveSet {
+        MoveSet::new()
+            .with(EraseRefill, params.moves.erase_refill)
+            .with(GrowShared, params.moves.grow_shared)
+            .with(RegionErase::new(), params.moves.region_erase)
+    }
+
+    pub fn name(&self, i: usize) -> &'static str {
+        self.moves[i].0.name()
+    }
+
+    pub fn weight(&self, i: usize) -> f64 {
+        self.moves[i].1
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Zeroed statistics in the order of the moves.
+    pub fn new_stats(&self) -> Vec<MoveStats> {
+        self.moves
+            .iter()
+            .map(|(m, _)| MoveStats {
+                name: m.name().to_owned(),
+                tried: 0,
+                accepted: 0,
+                improved: 0,
+            })
+            .collect()
+    }
+
+    /// Draws a move by weight.
+    pub fn choose(&self, rng: &mut Mcg128Xsl64) -> usize {
+        let total: f64 = self.moves.iter().map(|&(_, w)| w).sum();
+        assert!(total > 0.0, "every move has weight 0");
+        // 重みのある手が 1 つだけなら乱数を引かない (以前と同じ列になる)
+        if self.moves.iter().filter(|&&(_, w)| w > 0.0).count() == 1 {
+            return self.moves.iter().position(|&(_, w)| w > 0.0).unwrap();
+        }
+        let mut r = rng.gen_range(0.0, total);
+        for (i, &(_, w)) in self.moves.iter().enumerate() {
+            if r < w {
+                return i;
+            }
+            r -= w;
+        }
+        self.moves.iter().rposition(|&(_, w)| w > 0.0).unwrap()
+    }
+
+    pub fn apply(
+        &mut self,
+        i: usize,
+        rng: &mut Mcg128Xsl64,
+        grid: &mut GridSystem,
+        params: &McParams,
+    ) {
+        self.moves[i].0.apply(rng, grid, params);
+    }
+
+    pub fn feedback(&mut self, i: usize, accepted: bool, improved: bool) {
+        self.moves[i].0.feedback(accepted, improved);
+    }
+
+    /// `Move::save` of every move.
+    pub fn save(&self) -> Vec<Vec<f64>> {
+        self.moves.iter().map(|(m, _)| m.save()).collect()
+    }
+
+    /// Takes back what `save` returned; a `saved` for another set of moves is ignored.
+    pub fn restore(&mut self, saved: &[Vec<f64>]) {
+        if saved.len() != self.moves.len() {
+            return;
+        }
+        for ((m, _), saved) in self.moves.iter_mut().zip(saved.iter()) {
+            m.restore(saved);
+        }
+    }
+}
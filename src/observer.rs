@@ -0,0 +1,169 @@
+use crate::{ArmStats, Budget, MoveStats};
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Where the search is at when an `Observer` is called.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Progress {
+    /// 何回目のリスタートか
+    pub run: u64,
+    /// リスタート内のステップ数
+    pub step: u32,
+    /// `SolveInput::start` からの経過時間
+    pub elapsed: Duration,
+    /// `improved` では新しいベスト、それ以外では今のリスタートの現在値
+    pub score: f64,
+    /// 共有ブロックの数
+    pub shared: usize,
+}
+
+/// Progress callbacks of `mc_solve_observed`. Every method does nothing by default.
+pub trait Observer {
+    fn restart_start(&mut self, _run: u64, _budget: &Budget) {}
+
+    fn restart_end(&mut self, _progress: &Progress) {}
+
+    /// Per-move statistics of the restart that just ended, right after `restart_end`.
+    fn move_stats(&mut self, _run: u64, _stats: &[MoveStats]) {}
+
+    /// Per-arm statistics of the restart that just ended, with `Selection::Bandit` only.
+    fn arm_stats(&mut self, _run: u64, _arms: &[ArmStats]) {}
+
+    /// The best score so far went down.
+    fn improved(&mut self, _progress: &Progress) {}
+
+    /// Called every 256 steps.
+    fn heartbeat(&mut self, _progress: &Progress) {}
+
+    /// Checked on each heartbeat; `true` ends the solve with the best answer so far.
+    fn should_stop(&mut self) -> bool {
+        false
+    }
+}
+
+impl Observer for () {}
+
+/// Stops the solve once the flag is set. The solve borrows it exclusively, so only the flag's
+/// value at the start (or another part of the same observer) can set it; to stop from another
+/// thread, pass `&AtomicBool` or `Arc<AtomicBool>` instead.
+impl Observer for AtomicBool {
+    fn should_stop(&mut self) -> bool {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+/// Stops the solve once another thread sets the flag.
+impl Observer for &AtomicBool {
+    fn should_stop(&mut self) -> bool {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+/// Stops the solve once another thread sets the flag.
+impl Observer for Arc<AtomicBool> {
+    fn should_stop(&mut self) -> bool {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+impl<T: Observer + ?Sized> Observer for &mut T {
+    fn restart_start(&mut self, run: u64, budget: &Budget) {
+        (**self).restart_start(run, budget)
+    }
+
+    fn restart_end(&mut self, progress: &Progress) {
+        (**self).restart_end(progress)
+    }
+
+    fn move_stats(&mut self, run: u64, stats: &[MoveStats]) {
+        (**self).move_stats(run, stats)
+    }
+
+    fn arm_stats(&mut self, run: u64, arms: &[ArmStats]) {
+        (**self).arm_stats(run, arms)
+    }
+
+    fn improved(&mut self, progress: &Progress) {
+        (**self).improved(progress)
+    }
+
+    fn heartbeat(&mut self, progress: &Progress) {
+        (**self).heartbeat(progress)
+    }
+
+    fn should_stop(&mut self) -> bool {
+        (**self).should_stop()
+    }
+}
+
+/// Sends every event to both observers and stops when either wants to.
+impl<A: Observer, B: Observer> Observer for (A, B) {
+    fn restart_start(&mut self, run: u64, budget: &Budget) {
+        self.0.restart_start(run, budget);
+        self.1.restart_start(run, budget);
+    }
+
+    fn restart_end(&mut self, progress: &Progress) {
+        self.0.restart_end(progress);
+        self.1.restart_end(progress);
+    }
+
+    fn move_stats(&mut self, run: u64, stats: &[MoveStats]) {
+        self.0.move_stats(run, stats);
+        self.1.move_stats(run, stats);
+    }
+
+    fn arm_stats(&mut self, run: u64, arms: &[ArmStats]) {
+        self.0.arm_stats(run, arms);
+        self.1.arm_stats(run, arms);
+    }
+
+    fn improved(&mut self, progress: &Progress) {
+        self.0.improved(progress);
+        self.1.improved(progress);
+    }
+
+    fn heartbeat(&mut self, progress: &Progress) {
+        self.0.heartbeat(progress);
+        self.1.heartbeat(progress);
+    }
+
+    fn should_stop(&mut self) -> bool {
+        // 両方に聞く
+        self.0.should_stop() | self.1.should_stop()
+    }
+}
+
+/// Writes each improvement as a CSV line `elapsed,run,step,score,shared`.
+pub struct Trace<W: Write> {
+    pub out: W,
+}
+
+impl<W: Write> Trace<W> {
+    pub fn new(mut out: W) -> Trace<W> {
+        writeln!(out, "elapsed,run,step,score,shared").ok();
+        Trace { out }
+    }
+}
+
+impl<W: Write> Observer for Trace<W> {
+    fn improved(&mut self, p: &Progress) {
+        // トレースが書けなくても解くのは止めない
+        writeln!(
+            self.out,
+            "{},{},{},{},{}",
+            p.elapsed.as_secs_f64(),
+            p.run,
+            p.step,
+            p.score,
+            p.shared
+        )
+        .ok();
+    }
+}
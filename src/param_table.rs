@@ -0,0 +1,162 @@
+use crate::{McParams, MoveWeights};
+use serde::{Deserialize, Serialize};
+use std::{fmt, io, path::Path, sync::OnceLock};
+
+/// One row of a `ParamTable`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParamEntry {
+    pub d: u8,
+    /// チューニングしたときの対数平均スコア (記録用)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_score: Option<f64>,
+    pub params: McParams,
+}
+
+/// `McParams` keyed by D, stored as a JSON array of `ParamEntry`.
+///
+/// A D between two rows gets their linear interpolation and a D outside the table gets the
+/// nearest row, so `get` never fails.
+#[derive(Debug, Clone)]
+pub struct ParamTable {
+    /// d の昇順で、空でなく重複もない
+    entries: Vec<ParamEntry>,
+}
+
+#[derive(Debug)]
+pub enum ParamTableError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Empty,
+    DuplicateD(u8),
+    ZeroMcRun(u8),
+    /// `erase_shared_p` is not a probability.
+    EraseSharedP(u8),
+    /// A move weight is negative, or every weight is 0.
+    MoveWeights(u8),
+}
+
+impl fmt::Display for ParamTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamTableError::Io(e) => write!(f, "{}", e),
+            ParamTableError::Json(e) => write!(f, "{}", e),
+            ParamTableError::Empty => write!(f, "parameter table is empty"),
+            ParamTableError::DuplicateD(d) => write!(f, "D={} appears more than once", d),
+            ParamTableError::ZeroMcRun(d) => write!(f, "mc_run of D={} is 0", d),
+            ParamTableError::EraseSharedP(d) => {
+                write!(f, "erase_shared_p of D={} is not in [0, 1]", d)
+            }
+            ParamTableError::MoveWeights(d) => write!(
+                f,
+                "move weights of D={} must be non-negative and not all 0",
+                d
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParamTableError {}
+
+impl From<io::Error> for ParamTableError {
+    fn from(e: io::Error) -> Self {
+        ParamTableError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ParamTableError {
+    fn from(e: serde_json::Error) -> Self {
+        ParamTableError::Json(e)
+    }
+}
+
+impl ParamTable {
+    pub fn new(mut entries: Vec<ParamEntry>) -> Result<ParamTable, ParamTableError> {
+        entries.sort_by_key(|e| e.d);
+        if entries.is_empty() {
+            return Err(ParamTableError::Empty);
+        }
+        for w in entries.windows(2) {
+            if w[0].d == w[1].d {
+                return Err(ParamTableError::DuplicateD(w[0].d));
+            }
+        }
+        if let Some(e) = entries.iter().find(|e| e.params.mc_run == 0) {
+            return Err(ParamTableError::ZeroMcRun(e.d));
+        }
+        for e in entries.iter() {
+            if !(0.0..=1.0).contains(&e.params.erase_shared_p) {
+                return Err(ParamTableError::EraseSharedP(e.d));
+            }
+            // MoveSet::choose は重みの合計が正でないと引けない
+            let MoveWeights {
+                erase_refill,
+                grow_shared,
+                region_erase,
+            } = e.params.moves;
+            let weights = [erase_refill, grow_shared, region_erase];
+            if !weights.iter().all(|&w| w >= 0.0) || !weights.iter().any(|&w| w > 0.0) {
+                return Err(ParamTableError::MoveWeights(e.d));
+            }
+        }
+        Ok(ParamTable { entries })
+    }
+
+    pub fn from_json(json: &str) -> Result<ParamTable, ParamTableError> {
+        ParamTable::new(serde_json::from_str(json)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<ParamTable, ParamTableError> {
+        ParamTable::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// The table in `src/params.json`, compiled into the binary.
+    pub fn embedded() -> &'static ParamTable {
+        static TABLE: OnceLock<ParamTable> = OnceLock::new();
+        TABLE.get_or_init(|| {
+            ParamTable::from_json(include_str!("params.json")).expect("src/params.json is broken")
+        })
+    }
+
+    pub fn entries(&self) -> &[ParamEntry] {
+        &self.entries
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.entries).unwrap()
+    }
+
+    pub fn get(&self, d: u8) -> McParams {
+        let i = self.entries.partition_point(|e| e.d < d);
+        if i == self.entries.len() {
+            return self.entries[i - 1].params;
+        }
+        let hi = &self.entries[i];
+        if hi.d == d || i == 0 {
+            return hi.params;
+        }
+        let lo = &self.entries[i - 1];
+        let t = (d - lo.d) as f64 / (hi.d - lo.d) as f64;
+        interpolate(&lo.params, &hi.params, t)
+    }
+}
+
+/// 数値は線形補間、列挙型は近い方を取る
+fn interpolate(a: &McParams, b: &McParams, t: f64) -> McParams {
+    let f = |x: f64, y: f64| x + (y - x) * t;
+    let near = if t < 0.5 { a } else { b };
+    McParams {
+        mc_run: f(a.mc_run as f64, b.mc_run as f64).round() as u64,
+        erase_small_th: f(a.erase_small_th as f64, b.erase_small_th as f64).round() as usize,
+        erase_shared_p: f(a.erase_shared_p, b.erase_shared_p),
+        moves: MoveWeights {
+            erase_refill: f(a.moves.erase_refill, b.moves.erase_refill),
+            grow_shared: f(a.moves.grow_shared, b.moves.grow_shared),
+            region_erase: f(a.moves.region_erase, b.moves.region_erase),
+        },
+        acceptance: near.acceptance,
+        start_temp: f(a.start_temp, b.start_temp),
+        end_temp: f(a.end_temp, b.end_temp),
+        schedule: near.schedule,
+        selection: near.selection,
+    }
+}
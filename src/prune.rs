@@ -0,0 +1,208 @@
+use crate::SolveResult;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// One object of a `SolveResult` with the number of cubes on each silhouette pixel.
+struct Object<'a> {
+    d: usize,
+    grid: &'a mut [u16],
+    /// x * d + z
+    front: Vec<u32>,
+    /// z * d + y
+    right: Vec<u32>,
+}
+
+impl<'a> Object<'a> {
+    fn new(d: usize, grid: &'a mut [u16]) -> Object<'a> {
+        let mut object = Object {
+            d,
+            grid,
+            front: vec![0; d * d],
+            right: vec![0; d * d],
+        };
+        for i in 0..object.grid.len() {
+            if is_cube(object.grid[i]) {
+                let (f, r) = object.pixels(i);
+                object.front[f] += 1;
+                object.right[r] += 1;
+            }
+        }
+        object
+    }
+
+    fn pixels(&self, i: usize) -> (usize, usize) {
+        let d = self.d;
+        let (x, y, z) = (i / (d * d), i / d % d, i % d);
+        (x * d + z, z * d + y)
+    }
+
+    fn neighbours(&self, i: usize) -> impl Iterator<Item = usize> {
+        let d = self.d;
+        let (x, y, z) = (i / (d * d), i / d % d, i % d);
+        tools::D3.iter().filter_map(move |&(dx, dy, dz)| {
+            let (x, y, z) = (x.wrapping_add(dx), y.wrapping_add(dy), z.wrapping_add(dz));
+            if x < d && y < d && z < d {
+                Some((x * d + y) * d + z)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Both pixels of cube `i` are covered by some other cube too.
+    fn redundant(&self, i: usize) -> bool {
+        let (f, r) = self.pixels(i);
+        self.front[f] > 1 && self.right[r] > 1
+    }
+
+    /// The block of cube `i` stays connected without it.
+    fn stays_connected(&self, i: usize) -> bool {
+        let id = self.grid[i];
+        let around: Vec<_> = self.neighbours(i).filter(|&j| self.grid[j] == id).collect();
+        if around.len() <= 1 {
+            return true;
+        }
+        // 隣の 1 つから i を通らずに残りの隣へ全部たどり着ければよい
+        let mut rest = around.len() - 1;
+        let mut visited = HashSet::new();
+        visited.insert(i);
+        visited.insert(around[0]);
+        let mut stack = vec![around[0]];
+        while let Some(j) = stack.pop() {
+            for k in self.neighbours(j) {
+                if self.grid[k] == id && visited.insert(k) {
+                    if around.contains(&k) {
+                        rest -= 1;
+                        if rest == 0 {
+                            return true;
+                        }
+                    }
+                    stack.push(k);
+                }
+            }
+        }
+        false
+    }
+
+    fn remove(&mut self, i: usize) {
+        let (f, r) = self.pixels(i);
+        self.front[f] -= 1;
+        self.right[r] -= 1;
+        self.grid[i] = 0;
+    }
+
+    fn put(&mut self, i: usize, id: u16) {
+        let (f, r) = self.pixels(i);
+        self.front[f] += 1;
+        self.right[r] += 1;
+        self.grid[i] = id;
+    }
+
+    fn covered(&self, i: usize) -> bool {
+        let (f, r) = self.pixels(i);
+        self.front[f] > 0 && self.right[r] > 0
+    }
+}
+
+fn is_cube(id: u16) -> bool {
+    id != 0 && id != !0
+}
+
+impl SolveResult {
+    /// Removes cubes the silhouettes do not need and sets `score` to that of the answer.
+    ///
+    /// Unshared cubes go first, one point each, as long as their front and right pixels stay
+    /// covered and their block stays connected. A smaller shared block would cost more, so
+    /// shared blocks are only removed whole, from every object at once, when none of their
+    /// cubes is needed; the remaining copies stay congruent.
+    pub fn prune(&mut self) {
+        if self.grids.is_empty() {
+            return;
+        }
+        let d = self.d();
+        let n = self.grids.len();
+        let mut used_by: HashMap<u16, usize> = HashMap::new();
+        for grid in self.grids.iter() {
+            let ids: HashSet<u16> = grid.iter().copied().filter(|&id| is_cube(id)).collect();
+            for id in ids {
+                *used_by.entry(id).or_insert(0) += 1;
+            }
+        }
+        let mut objects: Vec<_> = self.grids.iter_mut().map(|g| Object::new(d, g)).collect();
+
+        // 消すと関節が端になることがあるので、変化がなくなるまで繰り返す
+        for object in objects.iter_mut() {
+            loop {
+                let mut removed = false;
+                for i in 0..object.grid.len() {
+                    let id = object.grid[i];
+                    if !is_cube(id) || used_by[&id] > 1 {
+                        continue;
+                    }
+                    if object.redundant(i) && object.stays_connected(i) {
+                        object.remove(i);
+                        removed = true;
+                    }
+                }
+                if !removed {
+                    break;
+                }
+            }
+        }
+
+        // 小さいブロックほど消したときに得をする
+        let mut cells: HashMap<u16, Vec<Vec<usize>>> = HashMap::new();
+        for (k, object) in objects.iter().enumerate() {
+            for (i, &id) in object.grid.iter().enumerate() {
+                if is_cube(id) && used_by[&id] > 1 {
+                    cells.entry(id).or_insert_with(|| vec![vec![]; n])[k].push(i);
+                }
+            }
+        }
+        let mut shared: Vec<_> = cells.into_iter().collect();
+        shared.sort_by_key(|(id, cells)| (cells.iter().map(|c| c.len()).max(), *id));
+        for (id, cells) in shared {
+            for (object, cells) in objects.iter_mut().zip(cells.iter()) {
+                for &i in cells {
+                    object.remove(i);
+                }
+            }
+            let needed = objects
+                .iter()
+                .zip(cells.iter())
+                .any(|(object, cells)| cells.iter().any(|&i| !object.covered(i)));
+            if needed {
+                for (object, cells) in objects.iter_mut().zip(cells.iter()) {
+                    for &i in cells {
+                        object.put(i, id);
+                    }
+                }
+            }
+        }
+        self.score = self.exact_score();
+    }
+
+    /// The score `tools::compute_score` gives, divided by 1e9.
+    ///
+    /// 探索中の点数は片側ブロックに 1 / size を足しているので、それとは少し違う
+    pub fn exact_score(&self) -> f64 {
+        let mut blocks: BTreeMap<u16, Vec<usize>> = BTreeMap::new();
+        for (k, grid) in self.grids.iter().enumerate() {
+            for &id in grid.iter().filter(|&&id| is_cube(id)) {
+                blocks
+                    .entry(id)
+                    .or_insert_with(|| vec![0; self.grids.len()])[k] += 1;
+            }
+        }
+        blocks
+            .values()
+            .map(|counts| {
+                let used: Vec<_> = counts.iter().filter(|&&c| c > 0).collect();
+                if used.len() == 1 {
+                    *used[0] as f64
+                } else {
+                    1.0 / *used[0] as f64
+                }
+            })
+            .sum()
+    }
+}
@@ -1,15 +1,32 @@
-use crate::{AxisMap, BlockSet, Grid3, GridFront, GridRight, McParams, Point};
+use crate::{
+    bandit_arms, choose_arm, is_half_id, update_arms, Acceptance, ArmStats, AxisMap, BlockSet,
+    Budget, Grid3, GridFront, GridRight, McParams, MoveSet, MoveStats, Observer, Placement, Point,
+    Progress, Selection,
+};
 use rand::{seq::SliceRandom, Rng};
 use rand_pcg::Mcg128Xsl64;
+use serde::{Deserialize, Serialize};
 use smallvec::{smallvec, SmallVec};
-use std::time::{Duration, Instant};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// `GridBox` への変更を取り消すための記録
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Change {
+    Put(Point),
+    Remove(Point, u16),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct GridBox {
     d: u8,
     grid: Grid3<u16>,
     front: GridFront<u8>,
     right: GridRight<u8>,
+    #[serde(skip)]
+    journal: Vec<Change>,
 }
 
 pub struct YetPointSet {
@@ -27,10 +44,10 @@ pub struct Hole {
     pub right: Vec<usize>,
 }
 
-#[derive(Clone)]
+/// The objects being filled and the blocks placed in them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GridSystem {
-    grid_1: GridBox,
-    grid_2: GridBox,
+    grids: Vec<GridBox>,
     block: BlockSet,
 }
 
@@ -71,6 +88,7 @@ impl GridBox {
             grid,
             front,
             right,
+            journal: Vec::new(),
         }
     }
 
@@ -84,6 +102,7 @@ impl GridBox {
         for &i in hole.right.iter() {
             self.right.data[i] = 0;
         }
+        self.journal.clear();
     }
 
     pub fn make_hole(&self) -> Hole {
@@ -166,20 +185,55 @@ impl GridBox {
         YetPointSet { yet_yet, yet, can }
     }
 
+    pub fn d(&self) -> u8 {
+        self.d
+    }
+
+    /// Block ids in `Grid3` order: 0 for empty cells and `!0` for cells outside the silhouettes.
+    pub fn data(&self) -> &[u16] {
+        &self.grid.data
+    }
+
     pub fn put(&mut self, p: Point, block_id: u16) {
+        debug_assert_eq!(self.grid[p], 0);
         self.grid[p] = block_id;
         self.front[p] += 1;
         self.right[p] += 1;
+        self.journal.push(Change::Put(p));
     }
 
     pub fn remove(&mut self, p: Point) {
         debug_assert_ne!(self.grid[p], 0);
         debug_assert!(self.front[p] > 0);
         debug_assert!(self.right[p] > 0);
+        self.journal.push(Change::Remove(p, self.grid[p]));
         self.grid[p] = 0;
         self.front[p] -= 1;
         self.right[p] -= 1;
     }
+
+    /// Forget the changes recorded since the last commit/rollback.
+    pub fn commit(&mut self) {
+        self.journal.clear();
+    }
+
+    /// Undo every `put`/`remove` since the last commit/rollback.
+    pub fn rollback(&mut self) {
+        while let Some(change) = self.journal.pop() {
+            match change {
+                Change::Put(p) => {
+                    self.grid[p] = 0;
+                    self.front[p] -= 1;
+                    self.right[p] -= 1;
+                }
+                Change::Remove(p, block_id) => {
+                    self.grid[p] = block_id;
+                    self.front[p] += 1;
+                    self.right[p] += 1;
+                }
+            }
+        }
+    }
 }
 
 impl YetPointSet {
@@ -201,85 +255,278 @@ impl YetPointSet {
 }
 
 impl GridSystem {
-    pub fn erase_half(&mut self, erase_small_th: usize) {
-        for &p in self.block.half1.iter() {
-            self.grid_1.remove(p);
+    pub fn new(d: u8, input: &SolveInput) -> GridSystem {
+        GridSystem {
+            grids: input
+                .objects
+                .iter()
+                .map(|o| GridBox::new(d, &o.front, &o.right))
+                .collect(),
+            block: BlockSet::new(input.objects.len()),
         }
-        for &p in self.block.half2.iter() {
-            self.grid_2.remove(p);
+    }
+
+    pub fn commit(&mut self) {
+        for grid in self.grids.iter_mut() {
+            grid.commit();
+        }
+        self.block.commit();
+    }
+
+    pub fn rollback(&mut self) {
+        for grid in self.grids.iter_mut() {
+            grid.rollback();
+        }
+        self.block.rollback();
+    }
+
+    fn remove_cells(&mut self, cells: Vec<Vec<Point>>) {
+        for (grid, cells) in self.grids.iter_mut().zip(cells) {
+            for p in cells {
+                grid.remove(p);
+            }
+        }
+    }
+
+    pub fn erase_half(&mut self, erase_small_th: usize) {
+        for (grid, half) in self.grids.iter_mut().zip(self.block.half.iter()) {
+            for &p in half.iter() {
+                grid.remove(p);
+            }
         }
         self.block.half_reset();
         if erase_small_th > 0 {
-            while let Some((p1, p2)) = self.block.pop_small(erase_small_th) {
-                for p in p1 {
-                    self.grid_1.remove(p);
-                }
-                for p in p2 {
-                    self.grid_2.remove(p);
-                }
+            while let Some(cells) = self.block.pop_small(erase_small_th) {
+                self.remove_cells(cells);
+            }
+        }
+    }
+
+    /// Erases shared block `id` from every object, if there is one.
+    pub fn erase_shared_id(&mut self, id: u16) -> bool {
+        match self.block.pop_id(id) {
+            Some(cells) => {
+                self.remove_cells(cells);
+                true
             }
+            None => false,
         }
     }
 
     pub fn erase_shared(&mut self, rng: &mut Mcg128Xsl64, erase_shared_p: f64) {
         if !self.block.shared.is_empty() && rng.gen_bool(erase_shared_p) {
-            let (p1, p2) = self.block.pop_random(rng);
-            for p in p1 {
-                self.grid_1.remove(p);
+            let cells = self.block.pop_random(rng);
+            self.remove_cells(cells);
+        }
+    }
+
+    pub fn block(&self) -> &BlockSet {
+        &self.block
+    }
+
+    pub fn grids(&self) -> &[GridBox] {
+        &self.grids
+    }
+
+    pub fn make_holes(&self) -> Vec<Hole> {
+        self.grids.iter().map(|g| g.make_hole()).collect()
+    }
+
+    /// Resets every object to its empty state.
+    pub fn reset(&mut self, holes: &[Hole]) {
+        for (grid, hole) in self.grids.iter_mut().zip(holes) {
+            grid.reset(hole);
+        }
+        self.block.reset();
+    }
+
+    /// Fills every remaining silhouette pixel once, returning the score of the new blocks.
+    pub fn fill(&mut self, rng: &mut Mcg128Xsl64, holes: &[Hole]) -> Option<f64> {
+        fill_all(rng, holes, self, 1e100)
+    }
+
+    /// Rebuilds the blocks of `result`, which must be an answer for `input`.
+    ///
+    /// A block in two or more objects becomes a shared block, any other a half block.
+    pub fn from_result(d: u8, input: &SolveInput, result: &SolveResult) -> GridSystem {
+        let mut grid = GridSystem::new(d, input);
+        let n = grid.grids.len();
+        let mut blocks: BTreeMap<u16, Vec<Vec<Point>>> = BTreeMap::new();
+        for (k, data) in result.grids.iter().enumerate() {
+            for x in 0..d {
+                for y in 0..d {
+                    for z in 0..d {
+                        let id =
+                            data[(x as usize * d as usize + y as usize) * d as usize + z as usize];
+                        if id != 0 && id != !0 {
+                            blocks.entry(id).or_insert_with(|| vec![vec![]; n])[k]
+                                .push(Point::new(x, y, z));
+                        }
+                    }
+                }
             }
-            for p in p2 {
-                self.grid_2.remove(p);
+        }
+        for cells in blocks.into_values() {
+            let used: SmallVec<[usize; 4]> = (0..n).filter(|&k| !cells[k].is_empty()).collect();
+            if let [k] = used[..] {
+                let id = grid.block.gen_half_block_id();
+                for &p in cells[k].iter() {
+                    grid.grids[k].put(p, id);
+                    grid.block.push_half(k, p);
+                }
+            } else {
+                let id = grid.block.gen_shared_block_id();
+                for &k in used.iter() {
+                    for &p in cells[k].iter() {
+                        grid.grids[k].put(p, id);
+                    }
+                }
+                grid.block.push_shared(id, cells);
             }
         }
+        grid.commit();
+        grid
     }
-}
 
-fn grow_shared_block(rng: &mut Mcg128Xsl64, grid: &mut GridSystem, p1: Point, p2: Point) -> f64 {
-    let block_id = grid.block.gen_shared_block_id();
-    let d = grid.grid_1.d;
-    let mut directions1 = [0, 1, 2, 3, 4, 5];
-    let mut directions2 = [0, 1, 2, 3, 4, 5];
-    directions1.shuffle(rng);
-    directions2.shuffle(rng);
-    let mut axis_map = AxisMap::new();
-    let mut pp1 = Vec::with_capacity(4);
-    let mut pp2 = Vec::with_capacity(4);
-    let mut stack: SmallVec<[_; 32]> = smallvec![(p1, p2)];
-    grid.grid_1.put(p1, block_id);
-    grid.grid_2.put(p2, block_id);
-    pp1.push(p1);
-    pp2.push(p2);
-    while let Some((p1, p2)) = stack.pop() {
-        for &dir1 in directions1.iter() {
-            if let Some(p1) = p1.next_cell(d, dir1) {
-                if grid.grid_1.grid[p1] != 0 {
+    /// Whether a shared block may take `p` of `object`: a free cell, or with `take_half` a
+    /// cell of a half block that stays connected without it.
+    fn can_take(&self, object: usize, p: Point, take_half: bool) -> bool {
+        let grid = &self.grids[object];
+        let id = grid.grid[p];
+        if id == 0 {
+            return true;
+        }
+        take_half
+            && is_half_id(id)
+            && (0..6)
+                .filter(|&dir| p.next_cell(grid.d, dir).is_some_and(|q| grid.grid[q] == id))
+                .count()
+                <= 1
+    }
+
+    /// Greedily adds cells to shared block `i`, the same cell of every copy under the
+    /// rotation between them, and returns how many cells each copy gained.
+    pub fn grow_shared(&mut self, i: usize, take_half: bool) -> usize {
+        let (id, cells) = &self.block.shared[i];
+        let id = *id;
+        let used: SmallVec<[usize; 4]> =
+            (0..cells.len()).filter(|&k| !cells[k].is_empty()).collect();
+        let lead = used[0];
+        let placements: SmallVec<[Placement; 4]> = used[1..]
+            .iter()
+            .map(|&k| {
+                Placement::find(&cells[lead], &cells[k])
+                    .expect("copies of a shared block are not congruent")
+            })
+            .collect();
+        let mut lead_cells = cells[lead].clone();
+        let d = self.grids[lead].d;
+        let mut grown = 0;
+        let mut next = 0;
+        while next < lead_cells.len() {
+            let c = lead_cells[next];
+            next += 1;
+            for dir in 0..6 {
+                let p = match c.next_cell(d, dir) {
+                    Some(p) if self.can_take(lead, p, take_half) => p,
+                    _ => continue,
+                };
+                let mut ps: SmallVec<[Point; 4]> = smallvec![p];
+                for (&object, placement) in used[1..].iter().zip(placements.iter()) {
+                    match placement.apply(p, d) {
+                        Some(q) if self.can_take(object, q, take_half) => ps.push(q),
+                        _ => break,
+                    }
+                }
+                if ps.len() < used.len() {
                     continue;
                 }
-                for dir2 in axis_map.map_axis(dir1, directions2) {
-                    if let Some(p2) = p2.next_cell(d, dir2) {
-                        if grid.grid_2.grid[p2] == 0 {
-                            grid.grid_1.put(p1, block_id);
-                            grid.grid_2.put(p2, block_id);
-                            pp1.push(p1);
-                            pp2.push(p2);
-                            axis_map = axis_map.fix(dir1, dir2);
-                            stack.push((p1, p2));
-                            break;
+                for (&object, &q) in used.iter().zip(ps.iter()) {
+                    if self.grids[object].grid[q] != 0 {
+                        self.grids[object].remove(q);
+                        self.block.take_half(object, q);
+                    }
+                    self.grids[object].put(q, id);
+                }
+                self.block.grow_shared(i, &ps);
+                lead_cells.push(p);
+                grown += 1;
+            }
+        }
+        grown
+    }
+}
+
+/// Grows one block from `starts` (object and cell) in all of those objects at once.
+fn grow_shared_block(
+    rng: &mut Mcg128Xsl64,
+    grid: &mut GridSystem,
+    starts: &[(usize, Point)],
+) -> f64 {
+    let block_id = grid.block.gen_shared_block_id();
+    let d = grid.grids[0].d;
+    // 先頭の物体の向きを基準にして、他の物体の向きは AxisMap で対応させる
+    let mut directions: SmallVec<[[u8; 6]; 4]> = SmallVec::new();
+    for _ in starts {
+        let mut dirs = [0, 1, 2, 3, 4, 5];
+        dirs.shuffle(rng);
+        directions.push(dirs);
+    }
+    let mut axis_maps: SmallVec<[AxisMap; 4]> = smallvec![AxisMap::new(); starts.len()];
+    let mut cells = vec![Vec::new(); grid.grids.len()];
+    let first: SmallVec<[Point; 4]> = starts.iter().map(|&(_, p)| p).collect();
+    for &(i, p) in starts {
+        grid.grids[i].put(p, block_id);
+        cells[i].push(p);
+    }
+    let mut stack: SmallVec<[_; 32]> = smallvec![first];
+    while let Some(ps) = stack.pop() {
+        for &dir1 in directions[0].iter() {
+            let q = match ps[0].next_cell(d, dir1) {
+                Some(q) if grid.grids[starts[0].0].grid[q] == 0 => q,
+                _ => continue,
+            };
+            let mut qs: SmallVec<[Point; 4]> = smallvec![q];
+            let mut dirs: SmallVec<[u8; 4]> = smallvec![dir1];
+            for (j, &(i, _)) in starts.iter().enumerate().skip(1) {
+                let found = axis_maps[j]
+                    .map_axis(dir1, directions[j])
+                    .into_iter()
+                    .find_map(|dir2| {
+                        let q = ps[j].next_cell(d, dir2)?;
+                        if grid.grids[i].grid[q] == 0 {
+                            Some((q, dir2))
+                        } else {
+                            None
                         }
+                    });
+                match found {
+                    Some((q, dir2)) => {
+                        qs.push(q);
+                        dirs.push(dir2);
                     }
+                    None => break,
                 }
             }
+            if qs.len() < starts.len() {
+                continue;
+            }
+            for (j, &(i, _)) in starts.iter().enumerate() {
+                grid.grids[i].put(qs[j], block_id);
+                cells[i].push(qs[j]);
+                axis_maps[j] = axis_maps[j].fix(dir1, dirs[j]);
+            }
+            stack.push(qs);
         }
     }
-    let size = pp1.len();
-    grid.block.push_shared(block_id, pp1, pp2);
+    let size = cells[starts[0].0].len();
+    grid.block.push_shared(block_id, cells);
     1.0 / size as f64
 }
 
 fn fill_all(
     rng: &mut Mcg128Xsl64,
-    hole_1: &[HoleXZYY],
-    hole_2: &[HoleXZYY],
+    holes: &[Hole],
     grid: &mut GridSystem,
     cut_off: f64,
 ) -> Option<f64> {
@@ -288,7 +535,7 @@ fn fill_all(
         p: Point,
         block: &mut BlockSet,
         cut_off: f64,
-        place: u8,
+        object: usize,
     ) -> f64 {
         if cut_off <= 2.0 {
             return 2.0;
@@ -297,13 +544,13 @@ fn fill_all(
         let mut c = 1.0;
         let mut stack = vec![p];
         grid.put(p, block_id);
-        block.push_half(place, p);
+        block.push_half(object, p);
         'OUT: while let Some(p) = stack.pop() {
             for dir in 0..6 {
                 if let Some(p) = p.next_cell(grid.d, dir) {
                     if grid.grid[p] == 0 {
                         grid.put(p, block_id);
-                        block.push_half(place, p);
+                        block.push_half(object, p);
                         c += 1.0;
                         if c + 1.0 / c >= cut_off {
                             break 'OUT;
@@ -321,91 +568,188 @@ fn fill_all(
         if score >= cut_off {
             return None;
         }
-        let yet1 = grid.grid_1.make_yet_points(hole_1);
-        let yet2 = grid.grid_2.make_yet_points(hole_2);
-        if yet1.satisfied() && yet2.satisfied() {
+        let yet: SmallVec<[YetPointSet; 4]> = grid
+            .grids
+            .iter()
+            .zip(holes)
+            .map(|(g, hole)| g.make_yet_points(&hole.x_z_yy))
+            .collect();
+        if yet.iter().all(|y| y.satisfied()) {
             break;
         }
-        let p1 = yet1.chose(rng);
-        let p2 = yet2.chose(rng);
-        match (p1, p2) {
-            (Some(p1), Some(p2)) => {
-                score += grow_shared_block(rng, grid, p1, p2);
-            }
-            (Some(p), None) => {
+        let starts: SmallVec<[(usize, Point); 4]> = yet
+            .iter()
+            .enumerate()
+            .filter_map(|(i, y)| y.chose(rng).map(|p| (i, p)))
+            .collect();
+        match starts[..] {
+            [] => return None,
+            [(i, p)] => {
                 score +=
-                    single_update_loop(&mut grid.grid_1, p, &mut grid.block, cut_off - score, 1);
+                    single_update_loop(&mut grid.grids[i], p, &mut grid.block, cut_off - score, i);
             }
-            (None, Some(p)) => {
-                score +=
-                    single_update_loop(&mut grid.grid_2, p, &mut grid.block, cut_off - score, 2);
-            }
-            (None, None) => return None,
+            _ => score += grow_shared_block(rng, grid, &starts),
         }
     }
     Some(score)
 }
 
+/// State of one `mc_run` restart, enough to continue it later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McState {
+    pub budget: Budget,
+    pub params: McParams,
+    /// 最後に時計を見たときの経過時間
+    pub elapsed: Duration,
+    pub step: u32,
+    pub score: f64,
+    pub need_erase: bool,
+    /// `MoveSet` の手ごとの集計
+    #[serde(default)]
+    pub moves: Vec<MoveStats>,
+    /// `Selection::Bandit` のときの腕ごとの記録
+    #[serde(default)]
+    pub arms: Vec<ArmStats>,
+    /// 最後のハートビートでの `MoveSet::save`
+    #[serde(default)]
+    pub move_states: Vec<Vec<f64>>,
+}
+
+impl McState {
+    pub fn new(budget: Budget, params: McParams) -> McState {
+        McState {
+            budget,
+            params,
+            elapsed: Duration::ZERO,
+            step: 0,
+            score: 1e100,
+            need_erase: true,
+            moves: Vec::new(),
+            arms: Vec::new(),
+            move_states: Vec::new(),
+        }
+    }
+}
+
+/// Why `mc_run` called `tick`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Tick {
+    /// 256 ステップごと
+    Heartbeat,
+    /// `best` が更新された
+    Improved,
+}
+
+/// Runs one restart until `state.budget` is used up or `tick` returns `true`.
+///
+/// Each step picks one move from `moves` as `params.selection` says, and its statistics go to
+/// `state.moves` (and `state.arms`). `tick` is called every 256 steps and whenever `best`
+/// improves.
 pub fn mc_run(
-    start: Instant,
-    limit: Duration,
+    state: &mut McState,
     rng: &mut Mcg128Xsl64,
-    hole_1: &Hole,
-    hole_2: &Hole,
+    holes: &[Hole],
     grid: &mut GridSystem,
+    moves: &mut MoveSet,
     best: &mut SolveResult,
-    params: McParams,
+    tick: &mut dyn FnMut(Tick, &McState, &GridSystem, &Mcg128Xsl64, &SolveResult) -> bool,
 ) -> u32 {
-    let mut score = 1e100;
-    let mut elapsed = start.elapsed();
-    let mut step = 0;
-    let mut need_erase = true;
+    let start = Instant::now()
+        .checked_sub(state.elapsed)
+        .unwrap_or_else(Instant::now);
+    let params = state.params;
+    if state.moves.is_empty() {
+        state.moves = moves.new_stats();
+    }
+    if state.arms.is_empty() && params.selection == Selection::Bandit {
+        state.arms = bandit_arms(moves, &params);
+    }
+    // 途中から再開するときは手が覚えていたことも戻す
+    if !state.move_states.is_empty() {
+        moves.restore(&state.move_states);
+    }
     loop {
-        step += 1;
-        elapsed = if step % 256 == 0 {
-            start.elapsed()
-        } else {
-            elapsed
-        };
-        if elapsed > limit {
-            break step;
+        if state.step.is_multiple_of(256) {
+            if state.budget.time.is_some() {
+                state.elapsed = start.elapsed();
+            }
+            state.move_states = moves.save();
+            if tick(Tick::Heartbeat, state, grid, rng, best) {
+                break state.step;
+            }
         }
+        if state.budget.is_over(state.elapsed, state.step as u64) {
+            break state.step;
+        }
+        state.step += 1;
 
-        if need_erase {
-            grid.erase_half(params.erase_small_th);
+        let (arm, m, step_params) = match params.selection {
+            Selection::Weighted => (None, moves.choose(rng), params),
+            Selection::Bandit => {
+                let arm = choose_arm(&state.arms);
+                let a = &state.arms[arm];
+                (Some(arm), a.index, a.params(&params))
+            }
+        };
+        if state.need_erase {
+            grid.erase_half(step_params.erase_small_th);
         }
-        let before_state = grid.clone();
+        grid.commit();
 
-        grid.erase_shared(rng, params.erase_shared_p);
+        moves.apply(m, rng, grid, &step_params);
+        state.moves[m].tried += 1;
 
+        // 焼きなましの受理判定は先に閾値を引いておき、それを cut_off に使う
+        let threshold = match params.acceptance {
+            Acceptance::HillClimb => state.score,
+            Acceptance::Annealing => {
+                let progress = state.budget.progress(state.elapsed, state.step as u64);
+                state.score - params.temperature(progress) * rng.gen::<f64>().ln()
+            }
+        };
         let sos = grid.block.shared_only_score();
-        let cut_off = score - sos;
-        let new_score =
-            sos + fill_all(rng, &hole_1.x_z_yy, &hole_2.x_z_yy, grid, cut_off).unwrap_or(1e100);
-        if new_score < score {
-            score = new_score;
-            best.set_best(&grid.grid_1, &grid.grid_2, score);
-            need_erase = true;
+        let cut_off = threshold - sos;
+        let new_score = sos + fill_all(rng, holes, grid, cut_off).unwrap_or(1e100);
+        let accepted = new_score < threshold && new_score < 1e100;
+        let improved = accepted && new_score < state.score;
+        state.moves[m].accepted += accepted as u64;
+        state.moves[m].improved += improved as u64;
+        moves.feedback(m, accepted, improved);
+        if let Some(arm) = arm {
+            update_arms(&mut state.arms, arm, accepted, improved);
+        }
+        if accepted {
+            state.score = new_score;
+            state.need_erase = true;
+            if best.set_best(&grid.grids, new_score) {
+                tick(Tick::Improved, state, grid, rng, best);
+            }
         } else {
-            *grid = before_state;
-            need_erase = false;
+            grid.rollback();
+            state.need_erase = false;
         }
     }
 }
 
+/// Front and right silhouettes of one object, as rows of `b'0'`/`b'1'` from `z = 0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Silhouette {
+    pub front: Vec<Vec<u8>>,
+    pub right: Vec<Vec<u8>>,
+}
+
 pub struct SolveInput {
     pub start: Instant,
-    pub limit: Duration,
-    pub front1: Vec<Vec<u8>>,
-    pub right1: Vec<Vec<u8>>,
-    pub front2: Vec<Vec<u8>>,
-    pub right2: Vec<Vec<u8>>,
+    pub budget: Budget,
+    /// コンテストでは 2 個
+    pub objects: Vec<Silhouette>,
     pub params: McParams,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SolveResult {
-    pub g1: Vec<u16>,
-    pub g2: Vec<u16>,
+    /// 物体ごとの block id
+    pub grids: Vec<Vec<u16>>,
     pub score: f64,
     pub run_count: u32,
 }
@@ -413,56 +757,245 @@ pub struct SolveResult {
 impl SolveResult {
     pub fn worst() -> SolveResult {
         SolveResult {
-            g1: Vec::new(),
-            g2: Vec::new(),
+            grids: Vec::new(),
             score: 1e300,
             run_count: 0,
         }
     }
 
-    pub fn set_best(&mut self, g1: &GridBox, g2: &GridBox, score: f64) -> bool {
+    pub fn set_best(&mut self, grids: &[GridBox], score: f64) -> bool {
         if score < self.score {
-            self.g1 = g1.grid.data.clone();
-            self.g2 = g2.grid.data.clone();
+            self.grids = grids.iter().map(|g| g.grid.data.clone()).collect();
             self.score = score;
             true
         } else {
             false
         }
     }
+
+    /// Keep the better of the two results and add up their run counts.
+    pub fn merge(&mut self, other: SolveResult) {
+        if other.score < self.score {
+            self.grids = other.grids;
+            self.score = other.score;
+        }
+        self.run_count += other.run_count;
+    }
 }
 
 pub fn mc_solve(rng: &mut Mcg128Xsl64, input: &SolveInput, d: u8) -> SolveResult {
-    let mut grid = GridSystem {
-        grid_1: GridBox::new(d, &input.front1, &input.right1),
-        grid_2: GridBox::new(d, &input.front2, &input.right2),
-        block: BlockSet::new(),
-    };
-    let hole_1 = grid.grid_1.make_hole();
-    let hole_2 = grid.grid_2.make_hole();
+    mc_solve_observed(rng, input, d, &mut ())
+}
+
+/// `mc_solve` that reports its progress to `observer` and stops when it asks to.
+pub fn mc_solve_observed(
+    rng: &mut Mcg128Xsl64,
+    input: &SolveInput,
+    d: u8,
+    observer: &mut dyn Observer,
+) -> SolveResult {
+    let budget = input.budget;
+    mc_solve_from(
+        rng,
+        input,
+        d,
+        budget,
+        input.params.mc_run,
+        None,
+        observer,
+        &mut |_| {},
+    )
+}
 
+/// `mc_run` restarts are spread over `threads` workers.
+///
+/// Each worker gets its own `Mcg128Xsl64` stream drawn from `seed` and its share of the step
+/// budget, and results are merged in worker order, so with `Budget::steps` a fixed seed and
+/// thread count always gives the same answer.
+pub fn mc_solve_parallel(threads: usize, seed: u128, input: &SolveInput, d: u8) -> SolveResult {
+    let threads = threads.max(1) as u64;
+    let mut master = Mcg128Xsl64::new(seed);
+    let mut done_runs = 0;
+    let jobs: Vec<_> = (0..threads)
+        .map(|i| {
+            let runs = input.params.mc_run / threads + u64::from(i < input.params.mc_run % threads);
+            done_runs += runs;
+            (Mcg128Xsl64::new(master.gen()), done_runs - runs, runs)
+        })
+        .filter(|&(_, _, runs)| runs > 0)
+        .collect();
+    let results: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = jobs
+            .into_iter()
+            .map(|(mut rng, first, runs)| {
+                let budget = Budget {
+                    steps: input.budget.steps.map(|steps| {
+                        // 累積で切り捨てるので、端数も含めて合計がちょうど steps になる
+                        let upto = |runs: u64| steps * runs / input.params.mc_run;
+                        upto(first + runs) - upto(first)
+                    }),
+                    ..input.budget
+                };
+                scope.spawn(move || mc_solve_runs(&mut rng, input, d, budget, runs))
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
     let mut best = SolveResult::worst();
-    for i in 0..input.params.mc_run {
-        let rest_run = input.params.mc_run - i;
-        if input.limit <= input.start.elapsed() {
-            break;
-        }
-        let total_mill = (input.limit - input.start.elapsed()).as_millis() as u64;
-        let sub_limit = Duration::from_millis(total_mill / rest_run);
+    for result in results {
+        best.merge(result);
+    }
+    best
+}
+
+/// Everything needed to continue `mc_solve_checkpointed` where it stopped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// `SolveInput::start` からの経過時間
+    pub elapsed: Duration,
+    /// 何回目のリスタートか
+    pub run: u64,
+    pub state: McState,
+    pub grid: GridSystem,
+    pub rng: Mcg128Xsl64,
+    pub best: SolveResult,
+}
+
+/// `mc_solve` that hands a `Snapshot` to `save` about every `interval` and can start from one.
+///
+/// When resuming, `rng` is replaced by the saved stream and the elapsed time carries over, so
+/// with `Budget::steps` the answer is the same as an uninterrupted run.
+pub fn mc_solve_checkpointed(
+    rng: &mut Mcg128Xsl64,
+    input: &SolveInput,
+    d: u8,
+    resume: Option<Snapshot>,
+    interval: Duration,
+    save: &mut dyn FnMut(&Snapshot),
+) -> SolveResult {
+    let mut last_save = Instant::now();
+    mc_solve_from(
+        rng,
+        input,
+        d,
+        input.budget,
+        input.params.mc_run,
+        resume,
+        &mut (),
+        &mut |snapshot| {
+            if last_save.elapsed() >= interval {
+                save(&snapshot());
+                last_save = Instant::now();
+            }
+        },
+    )
+}
+
+fn mc_solve_runs(
+    rng: &mut Mcg128Xsl64,
+    input: &SolveInput,
+    d: u8,
+    budget: Budget,
+    runs: u64,
+) -> SolveResult {
+    mc_solve_from(rng, input, d, budget, runs, None, &mut (), &mut |_| {})
+}
+
+#[allow(clippy::too_many_arguments)]
+fn mc_solve_from(
+    rng: &mut Mcg128Xsl64,
+    input: &SolveInput,
+    d: u8,
+    budget: Budget,
+    runs: u64,
+    resume: Option<Snapshot>,
+    observer: &mut dyn Observer,
+    tick: &mut dyn FnMut(&dyn Fn() -> Snapshot),
+) -> SolveResult {
+    let mut grid = GridSystem::new(d, input);
+    let holes = grid.make_holes();
+    let mut moves = MoveSet::from_params(&input.params);
+
+    let mut start = input.start;
+    let mut best = SolveResult::worst();
+    let mut first_run = 0;
+    let mut resumed = None;
+    if let Some(snapshot) = resume {
+        start = Instant::now()
+            .checked_sub(snapshot.elapsed)
+            .unwrap_or_else(Instant::now);
+        best = snapshot.best;
+        first_run = snapshot.run;
+        *rng = snapshot.rng;
+        grid = snapshot.grid;
+        resumed = Some(snapshot.state);
+    }
+    for i in first_run..runs {
+        let mut state = match resumed.take() {
+            Some(state) => state,
+            None => {
+                let rest_run = runs - i;
+                match budget.split(start.elapsed(), best.run_count as u64, rest_run) {
+                    Some(sub_budget) => {
+                        // リスタートごとに手の状態も最初からにする
+                        moves = MoveSet::from_params(&input.params);
+                        McState::new(sub_budget, input.params)
+                    }
+                    None => break,
+                }
+            }
+        };
+        observer.restart_start(i, &state.budget);
+        let mut stop = false;
         let step = mc_run(
-            Instant::now(),
-            sub_limit,
+            &mut state,
             rng,
-            &hole_1,
-            &hole_2,
+            &holes,
             &mut grid,
+            &mut moves,
             &mut best,
-            input.params,
+            &mut |event, state, grid, rng, best| {
+                let progress = |score| Progress {
+                    run: i,
+                    step: state.step,
+                    elapsed: start.elapsed(),
+                    score,
+                    shared: grid.block.shared.len(),
+                };
+                match event {
+                    Tick::Heartbeat => {
+                        observer.heartbeat(&progress(state.score));
+                        tick(&|| Snapshot {
+                            elapsed: start.elapsed(),
+                            run: i,
+                            state: state.clone(),
+                            grid: grid.clone(),
+                            rng: rng.clone(),
+                            best: best.clone(),
+                        });
+                        stop = observer.should_stop();
+                    }
+                    Tick::Improved => observer.improved(&progress(best.score)),
+                }
+                stop
+            },
         );
-        grid.grid_1.reset(&hole_1);
-        grid.grid_2.reset(&hole_2);
-        grid.block.reset();
+        observer.restart_end(&Progress {
+            run: i,
+            step,
+            elapsed: start.elapsed(),
+            score: state.score,
+            shared: grid.block.shared.len(),
+        });
+        observer.move_stats(i, &state.moves);
+        if !state.arms.is_empty() {
+            observer.arm_stats(i, &state.arms);
+        }
+        grid.reset(&holes);
         best.run_count += step;
+        if stop {
+            break;
+        }
     }
     best
 }
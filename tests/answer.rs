@@ -0,0 +1,13 @@
+use ahc019::SolveResult;
+
+#[test]
+fn empty_result_has_an_empty_output() {
+    // 時間切れで解が一つもないときも落ちない
+    let result = SolveResult::worst();
+    let output = result.to_output();
+    assert_eq!(output.n, 0);
+    assert!(output.b.is_empty());
+    let mut ans = vec![];
+    result.write_ans(&mut ans).unwrap();
+    assert_eq!(ans, b"0\n");
+}
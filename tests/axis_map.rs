@@ -0,0 +1,152 @@
+use ahc019::{AxisMap, GridSystem, Point, Rotation, SolveInput};
+use rand::Rng;
+use rand_pcg::Mcg128Xsl64;
+
+const ALL_DIRECTIONS: [u8; 6] = [0, 1, 2, 3, 4, 5];
+
+fn unit(direction: u8) -> [i32; 3] {
+    let mut v = [0; 3];
+    v[direction as usize / 2] = if direction & 1 == 0 { 1 } else { -1 };
+    v
+}
+
+/// Determinant of the matrix whose columns are the images of +x, +y, +z.
+fn determinant(rot: Rotation) -> i32 {
+    let [a, b, c] = [0, 2, 4].map(|d| unit(rot.apply_dir(d)));
+    a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+        + a[2] * (b[0] * c[1] - b[1] * c[0])
+}
+
+fn assert_consistent(map: AxisMap, fixed: &[(u8, u8)]) {
+    for &(d1, d2) in fixed {
+        match map {
+            AxisMap::None => panic!("fixed {:?} but map is None", fixed),
+            AxisMap::Map1 { from, to } => {
+                assert_eq!(d1 / 2, from / 2, "{:?} {:?}", map, fixed);
+                assert_eq!(d2, to ^ (from ^ d1), "{:?} {:?}", map, fixed);
+            }
+            AxisMap::Map2 { rot } => {
+                assert_eq!(determinant(rot), 1, "{:?}", rot);
+                for d in ALL_DIRECTIONS {
+                    assert_eq!(rot.apply_dir(d ^ 1), rot.apply_dir(d) ^ 1, "{:?}", rot);
+                }
+                assert_eq!(rot.apply_dir(d1), d2, "{:?} {:?}", map, fixed);
+            }
+        }
+    }
+}
+
+/// Follows every (dir1, dir2) that `map_axis` offers, up to `depth` steps.
+fn walk(map: AxisMap, fixed: &mut Vec<(u8, u8)>, depth: usize, count: &mut usize) {
+    assert_consistent(map, fixed);
+    *count += 1;
+    if depth == 0 {
+        return;
+    }
+    for dir1 in ALL_DIRECTIONS {
+        for dir2 in map.map_axis(dir1, ALL_DIRECTIONS) {
+            fixed.push((dir1, dir2));
+            walk(map.fix(dir1, dir2), fixed, depth - 1, count);
+            fixed.pop();
+        }
+    }
+}
+
+#[test]
+fn every_axis_map_sequence_is_a_proper_rotation() {
+    let mut count = 0;
+    walk(AxisMap::new(), &mut Vec::new(), 4, &mut count);
+    assert!(count > 10000);
+}
+
+#[test]
+fn map_axis_candidates_keep_the_order_of_directions() {
+    let directions = [3, 1, 4, 0, 5, 2];
+    for from in ALL_DIRECTIONS {
+        for to in ALL_DIRECTIONS {
+            let map = AxisMap::new().fix(from, to);
+            for dir1 in ALL_DIRECTIONS {
+                let candidates = map.map_axis(dir1, directions);
+                if dir1 / 2 == from / 2 {
+                    assert_eq!(candidates.len(), 1);
+                } else {
+                    assert_eq!(candidates.len(), 4);
+                    assert!(candidates.iter().all(|&d2| d2 / 2 != to / 2));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn rotation_group_is_closed() {
+    let all: Vec<_> = Rotation::all().collect();
+    assert_eq!(all.len(), 24);
+    for &a in all.iter() {
+        assert_eq!(determinant(a), 1);
+        assert_eq!(a.compose(a.inverse()), Rotation::IDENTITY);
+        for &b in all.iter() {
+            assert!(all.contains(&a.compose(b)));
+        }
+    }
+}
+
+#[test]
+fn is_same_accepts_rotations_and_rejects_mirror_images() {
+    // 鏡像と区別できるねじれた 4 個
+    let shape = [[0, 0, 0], [1, 0, 0], [1, 1, 0], [1, 1, 1]];
+    let to_tuples = |v: &[[i32; 3]]| -> Vec<(usize, usize, usize)> {
+        v.iter()
+            .map(|c| {
+                (
+                    (c[0] + 5) as usize,
+                    (c[1] + 5) as usize,
+                    (c[2] + 5) as usize,
+                )
+            })
+            .collect()
+    };
+    for rot in Rotation::all() {
+        let rotated: Vec<_> = shape.iter().map(|&c| rot.apply(c)).collect();
+        assert!(tools::is_same(&to_tuples(&shape), &to_tuples(&rotated)));
+        let mirrored: Vec<_> = rotated.iter().map(|c| [-c[0], c[1], c[2]]).collect();
+        assert!(!tools::is_same(&to_tuples(&shape), &to_tuples(&mirrored)));
+    }
+}
+
+fn to_tuples(points: &[Point]) -> Vec<(usize, usize, usize)> {
+    points
+        .iter()
+        .map(|p| {
+            let (x, y, z) = p.xyz();
+            (x as usize, y as usize, z as usize)
+        })
+        .collect()
+}
+
+#[test]
+fn grown_shared_blocks_are_congruent() {
+    let mut rng = Mcg128Xsl64::new(1);
+    let mut blocks = 0;
+    for seed in 1..=2000 {
+        let input = tools::gen(seed, Some(rng.gen_range(5, 10)));
+        let input = SolveInput::from_tools_input(&input).unwrap();
+        let d = input.d();
+        let mut grid = GridSystem::new(d, &input);
+        let holes = grid.make_holes();
+        assert!(grid.fill(&mut rng, &holes).is_some());
+        for (id, cells) in grid.block().shared.iter() {
+            let (pp1, pp2) = (&cells[0], &cells[1]);
+            assert!(
+                tools::is_same(&to_tuples(pp1), &to_tuples(pp2)),
+                "seed={} block={} {:?} {:?}",
+                seed,
+                id,
+                pp1,
+                pp2
+            );
+            blocks += 1;
+        }
+    }
+    assert!(blocks > 2000);
+}
@@ -0,0 +1,38 @@
+use ahc019::{brute_force, is_tiny, BruteForceParams, SolveInput};
+use std::time::Duration;
+
+#[test]
+fn brute_force_covers_a_tiny_input() {
+    // 角にある 2x2x2 の立方体 2 つは 8 個の共有ブロック 1 つで覆える
+    let silhouette = "11000\n11000\n00000\n00000\n00000\n";
+    let text = format!("5\n{}", silhouette.repeat(4));
+    let input = SolveInput::parse(&text).unwrap();
+    assert!(is_tiny(&input));
+    let params = BruteForceParams {
+        min_block_size: 1,
+        upper_bound: 1e100,
+        limit: Duration::from_secs(10),
+        ..Default::default()
+    };
+    let found = brute_force(&input, input.d(), params);
+    assert!(found.finished);
+    assert!(found.expanded > 0);
+    // 展開した状態数は MC のステップ数に入れない
+    assert_eq!(found.result.run_count, 0);
+    assert_eq!(found.result.score, 1.0 / 8.0);
+
+    let tools_input = tools::parse_input(&text);
+    let (score, err) = tools::compute_score(&tools_input, &found.result.to_output());
+    assert!(err.is_empty(), "{}", err);
+    assert_eq!(score, 125_000_000);
+}
+
+#[test]
+#[should_panic(expected = "two objects")]
+fn brute_force_rejects_extra_objects() {
+    let silhouette = "11000\n11000\n00000\n00000\n00000\n";
+    let input = SolveInput::parse(&format!("5\n{}", silhouette.repeat(6))).unwrap();
+    assert_eq!(input.objects.len(), 3);
+    assert!(!is_tiny(&input));
+    brute_force(&input, input.d(), BruteForceParams::default());
+}
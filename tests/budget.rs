@@ -0,0 +1,37 @@
+use ahc019::{mc_solve, mc_solve_parallel, Budget, SolveInput, SolveResult};
+use rand_pcg::Mcg128Xsl64;
+
+fn input(seed: u64) -> SolveInput {
+    let mut input = SolveInput::from_tools_input(&tools::gen(seed, Some(6))).unwrap();
+    input.budget = Budget::steps(300);
+    input.params.mc_run = 3;
+    input
+}
+
+fn assert_same(a: &SolveResult, b: &SolveResult) {
+    assert_eq!(a.grids, b.grids);
+    assert_eq!(a.score.to_bits(), b.score.to_bits());
+    assert_eq!(a.run_count, b.run_count);
+}
+
+#[test]
+fn step_budget_is_reproducible() {
+    for seed in 1..=5 {
+        let input = input(seed);
+        let d = input.d();
+        let a = mc_solve(&mut Mcg128Xsl64::new(7), &input, d);
+        let b = mc_solve(&mut Mcg128Xsl64::new(7), &input, d);
+        assert_eq!(a.run_count, 300);
+        assert_same(&a, &b);
+    }
+}
+
+#[test]
+fn parallel_step_budget_is_reproducible() {
+    let input = input(1);
+    let d = input.d();
+    let a = mc_solve_parallel(2, 7, &input, d);
+    let b = mc_solve_parallel(2, 7, &input, d);
+    assert_eq!(a.run_count, 300);
+    assert_same(&a, &b);
+}
@@ -0,0 +1,60 @@
+use ahc019::{mc_solve, mc_solve_checkpointed, Budget, McParams, Selection, Snapshot, SolveInput};
+use rand_pcg::Mcg128Xsl64;
+use std::time::Duration;
+
+fn assert_resumes(params: McParams) {
+    let mut input = SolveInput::from_tools_input(&tools::gen(3, Some(7))).unwrap();
+    input.budget = Budget::steps(2000);
+    input.params = McParams {
+        mc_run: 2,
+        ..params
+    };
+    let d = input.d();
+
+    let expected = mc_solve(&mut Mcg128Xsl64::new(11), &input, d);
+
+    let mut snapshots = Vec::new();
+    let saved = mc_solve_checkpointed(
+        &mut Mcg128Xsl64::new(11),
+        &input,
+        d,
+        None,
+        Duration::ZERO,
+        &mut |snapshot| snapshots.push(serde_json::to_string(snapshot).unwrap()),
+    );
+    assert_eq!(saved.grids, expected.grids);
+    assert!(snapshots.len() > 4);
+
+    // 1 回目のリスタートの途中と 2 回目のリスタートの途中から
+    for json in [&snapshots[1], &snapshots[snapshots.len() * 3 / 4]] {
+        let snapshot: Snapshot = serde_json::from_str(json).unwrap();
+        let resumed = mc_solve_checkpointed(
+            &mut Mcg128Xsl64::new(0),
+            &input,
+            d,
+            Some(snapshot),
+            Duration::from_secs(3600),
+            &mut |_| {},
+        );
+        assert_eq!(resumed.grids, expected.grids);
+        assert_eq!(resumed.score.to_bits(), expected.score.to_bits());
+        assert_eq!(resumed.run_count, expected.run_count);
+    }
+}
+
+#[test]
+fn resumed_run_matches_uninterrupted_run() {
+    let input = SolveInput::from_tools_input(&tools::gen(3, Some(7))).unwrap();
+    assert_resumes(input.params);
+}
+
+#[test]
+fn resumed_run_keeps_adaptive_moves() {
+    let input = SolveInput::from_tools_input(&tools::gen(3, Some(7))).unwrap();
+    // 熱いうちはほとんど受理されるので、領域が大きくなっていく
+    let mut params = input.params.annealing(1.0, 0.01);
+    params.moves.region_erase = 1.0;
+    assert_resumes(params);
+    params.selection = Selection::Bandit;
+    assert_resumes(params);
+}
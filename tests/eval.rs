@@ -0,0 +1,43 @@
+use ahc019::{summary_score, EvalRequest, McParams, Schedule};
+
+#[test]
+fn summary_of_no_cases_is_none() {
+    assert_eq!(summary_score(vec![]), None);
+    let (mean, log_mean, run_count) = summary_score(vec![(1.0, 10), (4.0, 20)]).unwrap();
+    assert_eq!(mean, 2.5);
+    assert!((log_mean - 2f64.ln()).abs() < 1e-12);
+    assert_eq!(run_count, 15.0);
+}
+
+#[test]
+fn request_carries_the_params() {
+    for d in [5, 8, 14] {
+        let mut params = McParams::opt(d);
+        params.moves.grow_shared = 0.25;
+        params.moves.region_erase = 0.5;
+        let annealing = params.annealing(0.05, 0.001);
+        let linear = McParams {
+            schedule: Schedule::Linear,
+            ..annealing
+        };
+        // EraseRefill を使わない表もそのまま運ぶ
+        let mut no_erase = params;
+        no_erase.moves.erase_refill = 0.0;
+        for params in [params, annealing, linear, no_erase] {
+            let request = EvalRequest::new(1, d as usize, &params);
+            assert_eq!(
+                serde_json::to_value(request.params()).unwrap(),
+                serde_json::to_value(params).unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+fn old_requests_keep_erase_refill() {
+    let request: EvalRequest = serde_json::from_str(
+        r#"{"seed": 1, "d": 5, "mc_run": 10, "erase_small_th": 3, "erase_shared_p": 0.5}"#,
+    )
+    .unwrap();
+    assert_eq!(request.params().moves.erase_refill, 1.0);
+}
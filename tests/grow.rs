@@ -0,0 +1,105 @@
+use ahc019::{mc_solve, Budget, GridSystem, Placement, Point, Rotation, SolveInput};
+use rand::Rng;
+use rand_pcg::Mcg128Xsl64;
+
+fn to_tuples(points: &[Point]) -> Vec<(usize, usize, usize)> {
+    points
+        .iter()
+        .map(|p| {
+            let (x, y, z) = p.xyz();
+            (x as usize, y as usize, z as usize)
+        })
+        .collect()
+}
+
+#[test]
+fn placement_is_recovered_from_cells() {
+    // L 字を回転して平行移動したものから向きを取り戻す
+    let from = [
+        Point::new(1, 1, 1),
+        Point::new(2, 1, 1),
+        Point::new(2, 2, 1),
+        Point::new(2, 2, 2),
+    ];
+    for rot in Rotation::all() {
+        let to: Vec<_> = from
+            .iter()
+            .map(|&p| {
+                let (x, y, z) = p.rotate(rot).xyz();
+                Point::new(x.wrapping_add(4), y.wrapping_add(4), z.wrapping_add(4))
+            })
+            .collect();
+        let placement = Placement::find(&from, &to).unwrap();
+        let mut mapped: Vec<_> = from
+            .iter()
+            .map(|&p| placement.apply(p, 8).unwrap())
+            .collect();
+        let mut to = to;
+        mapped.sort();
+        to.sort();
+        assert_eq!(mapped, to);
+    }
+    assert!(Placement::find(&from, &from[..3]).is_none());
+}
+
+#[test]
+fn grown_blocks_stay_congruent_and_roll_back() {
+    let mut rng = Mcg128Xsl64::new(5);
+    let mut grown = 0;
+    for seed in 1..=100 {
+        let input =
+            SolveInput::from_tools_input(&tools::gen(seed, Some(rng.gen_range(5, 10)))).unwrap();
+        let mut grid = GridSystem::new(input.d(), &input);
+        let holes = grid.make_holes();
+        grid.fill(&mut rng, &holes).unwrap();
+        grid.erase_half(0);
+        grid.commit();
+        let before = grid.clone();
+        for i in 0..grid.block().shared.len() {
+            grown += grid.grow_shared(i, false);
+        }
+        for (id, cells) in grid.block().shared.iter() {
+            assert!(
+                tools::is_same(&to_tuples(&cells[0]), &to_tuples(&cells[1])),
+                "seed={} block={}",
+                seed,
+                id
+            );
+            for (k, cells) in cells.iter().enumerate() {
+                for &p in cells.iter() {
+                    let (x, y, z) = p.xyz();
+                    let d = input.d() as usize;
+                    let i = (x as usize * d + y as usize) * d + z as usize;
+                    assert_eq!(grid.grids()[k].data()[i], *id);
+                }
+            }
+        }
+        grid.rollback();
+        assert_eq!(grid, before);
+    }
+    assert!(grown > 0);
+}
+
+#[test]
+fn polished_answers_stay_valid() {
+    let mut improved = 0;
+    for seed in 1..=20 {
+        let tools_input = tools::gen(seed, Some(8));
+        let mut input = SolveInput::from_tools_input(&tools_input).unwrap();
+        input.budget = Budget::steps(60);
+        input.params.mc_run = 2;
+        input.params.moves.grow_shared = 0.5;
+        let mut r = mc_solve(&mut Mcg128Xsl64::new(seed as u128), &input, input.d());
+        let (before, err) = tools::compute_score(&tools_input, &r.to_output());
+        assert!(err.is_empty(), "seed={}: {}", seed, err);
+        r.polish(&input);
+        let (score, err) = tools::compute_score(&tools_input, &r.to_output());
+        assert!(err.is_empty(), "seed={}: {}", seed, err);
+        assert_eq!(score, (r.score * 1e9).round() as i64, "seed={}", seed);
+        assert!(score <= before);
+        if score < before {
+            improved += 1;
+        }
+    }
+    assert!(improved > 0);
+}
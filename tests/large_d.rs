@@ -0,0 +1,57 @@
+use ahc019::{mc_solve, Budget, GridFront, GridRight, SolveInput, MAX_D};
+use rand_pcg::Mcg128Xsl64;
+
+#[test]
+fn faces_index_without_overflow() {
+    let d = MAX_D as u8;
+    let n = MAX_D * MAX_D;
+    let mut front = GridFront::from_vec(d, vec![0usize; n]);
+    let mut right = GridRight::from_vec(d, vec![0usize; n]);
+    for a in 0..d {
+        for b in 0..d {
+            front[(a, b)] = a as usize * 1000 + b as usize;
+            right[(a, b)] = a as usize * 1000 + b as usize;
+        }
+    }
+    // front は x * d + z、right は z * d + y の順に並ぶ
+    for a in 0..MAX_D {
+        for b in 0..MAX_D {
+            assert_eq!(front.data[a * MAX_D + b], a * 1000 + b);
+            assert_eq!(right.data[b * MAX_D + a], a * 1000 + b);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "needs 256 values")]
+fn wrong_length_is_rejected() {
+    GridFront::from_vec(16, vec![0u8; 255]);
+}
+
+#[test]
+fn deserializing_checks_length() {
+    let grid: Result<GridRight<u8>, _> = serde_json::from_str(r#"{"d": 2, "data": [0, 0, 0]}"#);
+    assert!(grid.is_err());
+    let grid: GridRight<u8> = serde_json::from_str(r#"{"d": 2, "data": [0, 0, 0, 1]}"#).unwrap();
+    assert_eq!(grid[(1, 1)], 1);
+}
+
+#[test]
+fn large_instances_are_solved() {
+    for (seed, d) in [(1, 16), (2, 20), (3, 32), (4, MAX_D)] {
+        let tools_input = tools::gen(seed, Some(d));
+        let mut input = SolveInput::from_tools_input(&tools_input).unwrap();
+        input.budget = Budget::steps(4);
+        input.params.mc_run = 1;
+        let result = mc_solve(&mut Mcg128Xsl64::new(seed as u128), &input, input.d());
+        let (score, err) = tools::compute_score(&tools_input, &result.to_output());
+        assert!(err.is_empty(), "D={}: {}", d, err);
+        // 探索中の点数は片側ブロックの扱いが違うので、出力から数え直した点数と比べる
+        assert_eq!(
+            score,
+            (result.exact_score() * 1e9).round() as i64,
+            "D={}",
+            d
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use ahc019::{
+    bandit_arms, choose_arm, mc_run, update_arms, Budget, EraseRefill, GridSystem, McParams,
+    McState, Move, MoveSet, RegionErase, Selection, SolveInput, SolveResult,
+};
+use rand_pcg::Mcg128Xsl64;
+
+/// 何も消さずに埋め直すだけの手
+struct Refill;
+
+impl Move for Refill {
+    fn name(&self) -> &'static str {
+        "refill"
+    }
+
+    fn apply(&mut self, _rng: &mut Mcg128Xsl64, _grid: &mut GridSystem, _params: &McParams) {}
+}
+
+#[test]
+fn custom_moves_are_drawn_by_weight() {
+    let tools_input = tools::gen(4, Some(7));
+    let input = SolveInput::from_tools_input(&tools_input).unwrap();
+    let mut grid = GridSystem::new(input.d(), &input);
+    let holes = grid.make_holes();
+    let mut state = McState::new(Budget::steps(2000), input.params);
+    let mut moves = MoveSet::new()
+        .with(EraseRefill, 3.0)
+        .with(Refill, 1.0)
+        .with(EraseRefill, 0.0);
+    let mut best = SolveResult::worst();
+    let mut rng = Mcg128Xsl64::new(1);
+    let steps = mc_run(
+        &mut state,
+        &mut rng,
+        &holes,
+        &mut grid,
+        &mut moves,
+        &mut best,
+        &mut |_, _, _, _, _| false,
+    );
+    assert_eq!(steps, 2000);
+
+    let names: Vec<_> = state.moves.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["erase_refill", "refill", "erase_refill"]);
+    let tried: Vec<_> = state.moves.iter().map(|s| s.tried).collect();
+    assert_eq!(tried.iter().sum::<u64>(), 2000);
+    assert!((1300..1700).contains(&tried[0]), "{:?}", tried);
+    assert_eq!(tried[2], 0);
+
+    let (score, err) = tools::compute_score(&tools_input, &best.to_output());
+    assert!(err.is_empty(), "{}", err);
+    assert!(score > 0);
+}
+
+#[test]
+fn region_erase_removes_every_block_it_touches() {
+    let input = SolveInput::from_tools_input(&tools::gen(2, Some(8))).unwrap();
+    let mut rng = Mcg128Xsl64::new(3);
+    let mut grid = GridSystem::new(input.d(), &input);
+    let holes = grid.make_holes();
+    grid.fill(&mut rng, &holes).unwrap();
+    grid.erase_half(0);
+    grid.commit();
+    assert!(!grid.block().shared.is_empty());
+    let before = grid.clone();
+
+    // どの形でも立方体全体を覆う大きさ
+    let mut region = RegionErase::new();
+    region.radius = 100.0;
+    region.apply(&mut rng, &mut grid, &input.params);
+    assert!(grid.block().shared.is_empty());
+    assert!(grid
+        .grids()
+        .iter()
+        .all(|g| g.data().iter().all(|&id| id == 0 || id == !0)));
+    grid.rollback();
+    assert_eq!(grid, before);
+
+    // 小さい領域は一部だけ消す
+    let mut region = RegionErase::new();
+    let n = grid.block().shared.len();
+    region.apply(&mut rng, &mut grid, &input.params);
+    assert!(grid.block().shared.len() < n);
+}
+
+#[test]
+fn region_size_follows_the_acceptance_rate() {
+    let input = SolveInput::from_tools_input(&tools::gen(2, Some(8))).unwrap();
+    let mut rng = Mcg128Xsl64::new(3);
+    let mut grid = GridSystem::new(input.d(), &input);
+    let mut region = RegionErase::new();
+    region.apply(&mut rng, &mut grid, &input.params);
+    for _ in 0..100 {
+        region.feedback(true, true);
+    }
+    assert_eq!(region.radius, 8.0);
+    for _ in 0..1000 {
+        region.feedback(false, false);
+    }
+    assert_eq!(region.radius, 1.0);
+    // 10 回に 1 回ほど受理されるなら大きさはおおよそ保たれる
+    region.radius = 4.0;
+    for i in 0..1000 {
+        region.feedback(i % 10 == 0, false);
+    }
+    assert!((2.0..8.0).contains(&region.radius), "{}", region.radius);
+}
+
+#[test]
+fn region_size_survives_a_bad_checkpoint() {
+    let mut region = RegionErase::new();
+    region.radius = 3.0;
+    region.restore(&[]);
+    region.restore(&[5.0]);
+    assert_eq!(region.radius, 3.0);
+    region.restore(&region.save());
+    assert_eq!(region.radius, 3.0);
+    region.restore(&[2.0, 8.0]);
+    assert_eq!(region.radius, 2.0);
+}
+
+#[test]
+fn bandit_arms_scale_the_erase_parameters() {
+    let moves = MoveSet::new()
+        .with(EraseRefill, 1.0)
+        .with(Refill, 0.0)
+        .with(RegionErase::new(), 2.0);
+    let params = McParams {
+        erase_small_th: 4,
+        erase_shared_p: 0.6,
+        ..Default::default()
+    };
+    let arms = bandit_arms(&moves, &params);
+    assert_eq!(arms.len(), 18);
+    assert!(arms.iter().all(|a| a.index != 1));
+    let ths: Vec<_> = arms.iter().map(|a| a.erase_small_th).collect();
+    assert!(ths.contains(&2) && ths.contains(&4) && ths.contains(&8));
+    assert!(arms.iter().all(|a| a.erase_shared_p <= 1.0));
+
+    // 0 は何倍しても 0 なので手ごとに 1 本にまとまる
+    let arms = bandit_arms(&moves, &McParams::default());
+    let names: Vec<_> = arms.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(names, ["erase_refill", "region_erase"]);
+}
+
+#[test]
+fn bandit_prefers_the_arm_that_improves() {
+    let moves = MoveSet::new().with(EraseRefill, 1.0).with(Refill, 1.0);
+    let mut arms = bandit_arms(&moves, &McParams::default());
+    for step in 0..2000 {
+        let arm = choose_arm(&arms);
+        // 1 本目は 10 回に 1 回改善し、2 本目は改善しない
+        let improved = arms[arm].index == 0 && step % 10 == 0;
+        update_arms(&mut arms, arm, improved, improved);
+    }
+    assert_eq!(arms.iter().map(|a| a.tried).sum::<u64>(), 2000);
+    assert!(arms[0].tried > 4 * arms[1].tried, "{:?}", arms);
+    assert!(arms[1].tried > 0);
+    assert!(arms[0].rate() > arms[1].rate());
+}
+
+#[test]
+fn bandit_steps_are_counted_per_arm() {
+    let tools_input = tools::gen(4, Some(7));
+    let input = SolveInput::from_tools_input(&tools_input).unwrap();
+    let mut grid = GridSystem::new(input.d(), &input);
+    let holes = grid.make_holes();
+    let params = McParams {
+        selection: Selection::Bandit,
+        ..input.params
+    };
+    let mut state = McState::new(Budget::steps(1000), params);
+    let mut moves = MoveSet::new().with(EraseRefill, 1.0).with(Refill, 1.0);
+    let mut best = SolveResult::worst();
+    let mut rng = Mcg128Xsl64::new(1);
+    mc_run(
+        &mut state,
+        &mut rng,
+        &holes,
+        &mut grid,
+        &mut moves,
+        &mut best,
+        &mut |_, _, _, _, _| false,
+    );
+
+    assert!(state.arms.len() > 2);
+    assert_eq!(state.arms.iter().map(|a| a.tried).sum::<u64>(), 1000);
+    for (i, s) in state.moves.iter().enumerate() {
+        let tried: u64 = state
+            .arms
+            .iter()
+            .filter(|a| a.index == i)
+            .map(|a| a.tried)
+            .sum();
+        assert_eq!(tried, s.tried);
+    }
+    for a in state.arms.iter() {
+        assert!(a.tried > 0);
+        assert!(a.improved <= a.accepted && a.accepted <= a.tried);
+    }
+    let (_, err) = tools::compute_score(&tools_input, &best.to_output());
+    assert!(err.is_empty(), "{}", err);
+}
@@ -0,0 +1,61 @@
+use ahc019::{lower_bound, mc_solve, Budget, InputError, SolveInput};
+use rand_pcg::Mcg128Xsl64;
+
+/// `tools::gen` の 2 物体に、別の seed の 1 個目を 3 個目として足す
+fn three_objects(seed: u64, d: usize) -> tools::Input {
+    let mut input = tools::gen(seed, Some(d));
+    let other = tools::gen(seed + 1000, Some(d));
+    input.f.push(other.f[0].clone());
+    input.r.push(other.r[0].clone());
+    input
+}
+
+#[test]
+fn three_objects_are_solved() {
+    for seed in 1..=5 {
+        let tools_input = three_objects(seed, 7);
+        let mut input = SolveInput::from_tools_input(&tools_input).unwrap();
+        assert_eq!(input.objects.len(), 3);
+        input.budget = Budget::steps(100);
+        input.params.mc_run = 2;
+        let result = mc_solve(&mut Mcg128Xsl64::new(seed as u128), &input, input.d());
+        let out = result.to_output();
+        assert_eq!(out.b.len(), 3);
+        let (score, err) = tools::compute_score(&tools_input, &out);
+        assert!(err.is_empty(), "seed={}: {}", seed, err);
+        // mc_solve のままの点数は片側ブロックの扱いが違う
+        let exact = result.exact_score();
+        assert_eq!(score, (exact * 1e9).round() as i64, "seed={}", seed);
+        assert!(lower_bound(&input) <= exact + 1e-9);
+    }
+}
+
+#[test]
+fn extra_objects_are_parsed() {
+    let tools_input = three_objects(1, 6);
+    let text = tools_input.to_string();
+    let input = SolveInput::parse(&text).unwrap();
+    assert_eq!(input.objects.len(), 3);
+    assert_eq!(tools::parse_input(&text).f.len(), 3);
+
+    // 物体の途中で終わっている
+    let lines: Vec<_> = text.lines().collect();
+    let cut = lines[..lines.len() - 3].join("\n");
+    assert!(SolveInput::parse(&cut).is_err());
+
+    // 2 物体の入力はこれまで通り
+    let two = tools::gen(1, Some(6)).to_string();
+    assert_eq!(SolveInput::parse(&two).unwrap().objects.len(), 2);
+}
+
+#[test]
+fn wrong_row_count_is_named() {
+    let mut tools_input = three_objects(2, 6);
+    tools_input.r[2].pop();
+    match SolveInput::from_tools_input(&tools_input) {
+        Err(e @ InputError::RowCount { .. }) => {
+            assert_eq!(e.to_string(), "right3 has 5 rows, expected D=6")
+        }
+        r => panic!("{:?}", r.map(|_| ())),
+    }
+}
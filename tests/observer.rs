@@ -0,0 +1,163 @@
+use ahc019::{
+    mc_solve, mc_solve_observed, ArmStats, Budget, MoveStats, Observer, Progress, Selection,
+    SolveInput,
+};
+use rand_pcg::Mcg128Xsl64;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Default)]
+struct Log {
+    starts: Vec<u64>,
+    ends: Vec<Progress>,
+    improved: Vec<Progress>,
+    heartbeats: usize,
+    moves: Vec<(u64, Vec<MoveStats>)>,
+    arms: Vec<(u64, Vec<ArmStats>)>,
+}
+
+impl Observer for Log {
+    fn restart_start(&mut self, run: u64, _budget: &Budget) {
+        self.starts.push(run);
+    }
+
+    fn restart_end(&mut self, progress: &Progress) {
+        self.ends.push(*progress);
+    }
+
+    fn improved(&mut self, progress: &Progress) {
+        self.improved.push(*progress);
+    }
+
+    fn heartbeat(&mut self, _progress: &Progress) {
+        self.heartbeats += 1;
+    }
+
+    fn move_stats(&mut self, run: u64, stats: &[MoveStats]) {
+        self.moves.push((run, stats.to_vec()));
+    }
+
+    fn arm_stats(&mut self, run: u64, arms: &[ArmStats]) {
+        self.arms.push((run, arms.to_vec()));
+    }
+}
+
+fn input() -> SolveInput {
+    let mut input = SolveInput::from_tools_input(&tools::gen(3, Some(6))).unwrap();
+    input.budget = Budget::steps(3000);
+    input.params.mc_run = 3;
+    input
+}
+
+#[test]
+fn observer_sees_every_restart_and_improvement() {
+    let input = input();
+    let d = input.d();
+    let mut log = Log::default();
+    let result = mc_solve_observed(&mut Mcg128Xsl64::new(7), &input, d, &mut log);
+
+    // 観測しても結果は変わらない
+    let plain = mc_solve(&mut Mcg128Xsl64::new(7), &input, d);
+    assert_eq!(result.grids, plain.grids);
+    assert_eq!(result.score.to_bits(), plain.score.to_bits());
+
+    assert_eq!(log.starts, vec![0, 1, 2]);
+    assert_eq!(log.ends.len(), 3);
+    let steps: u32 = log.ends.iter().map(|p| p.step).sum();
+    assert_eq!(steps, result.run_count);
+    assert!(log.heartbeats >= 3);
+
+    assert!(!log.improved.is_empty());
+    for w in log.improved.windows(2) {
+        assert!(w[1].score < w[0].score);
+    }
+    assert_eq!(log.improved.last().unwrap().score, result.score);
+
+    // 手ごとの集計はリスタートごとに届く
+    assert_eq!(log.moves.len(), 3);
+    for ((run, stats), end) in log.moves.iter().zip(log.ends.iter()) {
+        assert_eq!(*run, end.run);
+        let tried: u64 = stats.iter().map(|s| s.tried).sum();
+        assert_eq!(tried, end.step as u64);
+        assert_eq!(stats[0].name, "erase_refill");
+        for s in stats {
+            assert!(s.improved <= s.accepted && s.accepted <= s.tried);
+        }
+    }
+    assert!(log.arms.is_empty());
+}
+
+#[test]
+fn observer_sees_bandit_arms() {
+    let mut input = input();
+    input.params.selection = Selection::Bandit;
+    let d = input.d();
+    let mut log = Log::default();
+    let result = mc_solve_observed(&mut Mcg128Xsl64::new(7), &input, d, &mut log);
+    let again = mc_solve(&mut Mcg128Xsl64::new(7), &input, d);
+    assert_eq!(result.grids, again.grids);
+
+    assert_eq!(log.arms.len(), 3);
+    for ((run, arms), end) in log.arms.iter().zip(log.ends.iter()) {
+        assert_eq!(*run, end.run);
+        let tried: u64 = arms.iter().map(|a| a.tried).sum();
+        assert_eq!(tried, end.step as u64);
+        assert!(arms.iter().all(|a| a.name == "erase_refill"));
+    }
+}
+
+#[test]
+fn observer_can_stop_the_solve() {
+    let input = input();
+    let d = input.d();
+    let mut stop = (Log::default(), AtomicBool::new(true));
+    let result = mc_solve_observed(&mut Mcg128Xsl64::new(7), &input, d, &mut stop);
+    assert_eq!(stop.0.starts, vec![0]);
+    assert_eq!(result.run_count, 0);
+
+    stop.1.store(false, Ordering::Relaxed);
+    let result = mc_solve_observed(&mut Mcg128Xsl64::new(7), &input, d, &mut stop);
+    assert_eq!(result.run_count, 3000);
+}
+
+#[test]
+fn solve_can_be_stopped_from_another_thread() {
+    let mut input = input();
+    // 止めなければ 1 分かかる
+    input.budget = Budget::time(Duration::from_secs(60));
+    let d = input.d();
+
+    let flag = Arc::new(AtomicBool::new(false));
+    let setter = {
+        let flag = Arc::clone(&flag);
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            flag.store(true, Ordering::Relaxed);
+        })
+    };
+    let start = Instant::now();
+    let mut observer = Arc::clone(&flag);
+    let result = mc_solve_observed(&mut Mcg128Xsl64::new(7), &input, d, &mut observer);
+    setter.join().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert!(result.run_count > 0);
+
+    // 借用した旗でも同じ
+    let flag = AtomicBool::new(false);
+    let start = Instant::now();
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            thread::sleep(Duration::from_millis(100));
+            flag.store(true, Ordering::Relaxed);
+        });
+        let mut observer = &flag;
+        mc_solve_observed(&mut Mcg128Xsl64::new(7), &input, d, &mut observer);
+    });
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
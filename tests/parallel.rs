@@ -0,0 +1,25 @@
+use ahc019::{mc_solve_parallel, Budget, SolveInput};
+
+#[test]
+fn parallel_answers_are_valid_and_reproducible() {
+    for seed in 1..=4 {
+        let tools_input = tools::gen(seed, Some(7));
+        let mut input = SolveInput::from_tools_input(&tools_input).unwrap();
+        // 5 回のリスタートでは割り切れない
+        input.budget = Budget::steps(601);
+        input.params.mc_run = 5;
+        let d = input.d();
+        for threads in 1..=3 {
+            let a = mc_solve_parallel(threads, seed as u128, &input, d);
+            let b = mc_solve_parallel(threads, seed as u128, &input, d);
+            assert_eq!(a.grids, b.grids, "seed={} threads={}", seed, threads);
+            assert_eq!(a.score.to_bits(), b.score.to_bits());
+            // リスタートの数が割り切れなくてもステップは全部使う
+            assert_eq!(a.run_count, 601);
+
+            let (score, err) = tools::compute_score(&tools_input, &a.to_output());
+            assert!(err.is_empty(), "seed={} threads={}: {}", seed, threads, err);
+            assert!(score > 0);
+        }
+    }
+}
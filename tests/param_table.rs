@@ -0,0 +1,80 @@
+use ahc019::{McParams, ParamTable, ParamTableError};
+
+#[test]
+fn embedded_table_covers_the_contest_range() {
+    let table = ParamTable::embedded();
+    assert_eq!(table.entries().len(), 10);
+    let p = McParams::opt(6);
+    assert_eq!(p.mc_run, 100);
+    assert_eq!(p.erase_small_th, 8);
+    assert_eq!(p.erase_shared_p, 0.5271543071699281);
+
+    // 表の外は端の値
+    assert_eq!(McParams::opt(0).mc_run, 45);
+    assert_eq!(McParams::opt(64).mc_run, 17);
+    assert_eq!(McParams::opt(255).erase_small_th, 60);
+
+    let json = table.to_json();
+    let again = ParamTable::from_json(&json).unwrap();
+    assert_eq!(again.entries().len(), 10);
+}
+
+#[test]
+fn missing_d_is_interpolated() {
+    let table = ParamTable::from_json(
+        r#"[
+            {"d": 10, "params": {"mc_run": 30, "erase_small_th": 20, "erase_shared_p": 0.2}},
+            {"d": 6, "params": {"mc_run": 10, "erase_small_th": 10, "erase_shared_p": 0.6}}
+        ]"#,
+    )
+    .unwrap();
+    let p = table.get(8);
+    assert_eq!(p.mc_run, 20);
+    assert_eq!(p.erase_small_th, 15);
+    assert!((p.erase_shared_p - 0.4).abs() < 1e-12);
+    assert_eq!(table.get(6).mc_run, 10);
+    assert_eq!(table.get(5).mc_run, 10);
+    assert_eq!(table.get(11).mc_run, 30);
+}
+
+#[test]
+fn bad_tables_are_errors() {
+    assert!(matches!(
+        ParamTable::from_json("[]"),
+        Err(ParamTableError::Empty)
+    ));
+    assert!(matches!(
+        ParamTable::from_json(
+            r#"[{"d": 5, "params": {"mc_run": 1}}, {"d": 5, "params": {"mc_run": 2}}]"#
+        ),
+        Err(ParamTableError::DuplicateD(5))
+    ));
+    assert!(matches!(
+        ParamTable::from_json(r#"[{"d": 5, "params": {}}]"#),
+        Err(ParamTableError::ZeroMcRun(5))
+    ));
+    assert!(matches!(
+        ParamTable::from_json(r#"[{"d": 5, "params": {"mc_run": 1, "erase_shared_p": 1.5}}]"#),
+        Err(ParamTableError::EraseSharedP(5))
+    ));
+    assert!(matches!(
+        ParamTable::from_json(
+            r#"[{"d": 5, "params": {"mc_run": 1, "moves": {"erase_refill": 0}}}]"#
+        ),
+        Err(ParamTableError::MoveWeights(5))
+    ));
+    assert!(matches!(
+        ParamTable::from_json(
+            r#"[{"d": 5, "params": {"mc_run": 1, "moves": {"grow_shared": -1}}}]"#
+        ),
+        Err(ParamTableError::MoveWeights(5))
+    ));
+    assert!(matches!(
+        ParamTable::from_json("{"),
+        Err(ParamTableError::Json(_))
+    ));
+    assert!(matches!(
+        ParamTable::load("no/such/params.json"),
+        Err(ParamTableError::Io(_))
+    ));
+}
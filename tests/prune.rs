@@ -0,0 +1,91 @@
+use ahc019::{mc_solve, Budget, SolveInput, SolveResult};
+use rand_pcg::Mcg128Xsl64;
+
+const D: usize = 3;
+
+/// 物体ごとの `(x, y, z, id)` から `SolveResult` を作る
+fn result(objects: &[&[(usize, usize, usize, u16)]], score: f64) -> SolveResult {
+    SolveResult {
+        grids: objects
+            .iter()
+            .map(|cubes| {
+                let mut grid = vec![0; D * D * D];
+                for &(x, y, z, id) in cubes.iter() {
+                    grid[(x * D + y) * D + z] = id;
+                }
+                grid
+            })
+            .collect(),
+        score,
+        run_count: 0,
+    }
+}
+
+fn cubes(result: &SolveResult, object: usize) -> usize {
+    result.grids[object].iter().filter(|&&id| id != 0).count()
+}
+
+#[test]
+fn redundant_unshared_cube_is_removed() {
+    // 2x2 の正方形は 1 個消しても L 字でつながったまま
+    let square = [
+        (0, 0, 0, 10000),
+        (1, 0, 0, 10000),
+        (0, 1, 0, 10000),
+        (1, 1, 0, 10000),
+    ];
+    let single = [(0, 0, 0, 10001)];
+    // 探索中の点数は片側ブロックに 1 / size が乗っている
+    let mut r = result(&[&square, &single], 4.25 + 2.0);
+    r.prune();
+    assert_eq!(cubes(&r, 0), 3);
+    assert_eq!(cubes(&r, 1), 1);
+    assert_eq!(r.score, 4.0);
+}
+
+#[test]
+fn removal_keeps_blocks_connected() {
+    // 真ん中の (1, 0, 0) は両方の影が重なっているが、消すと L 字が切れる
+    let l1 = [(0, 0, 0, 10000), (1, 0, 0, 10000), (1, 1, 0, 10000)];
+    let l2 = [(0, 0, 0, 10001), (1, 0, 0, 10001), (1, 1, 0, 10001)];
+    let mut r = result(&[&l1, &l2], 6.0);
+    r.prune();
+    assert_eq!(cubes(&r, 0), 3);
+    assert_eq!(r.score, 6.0);
+}
+
+#[test]
+fn unneeded_shared_block_is_removed_whole() {
+    // ブロック 1 (1 個) の影はブロック 2 の L 字がすべて覆っている
+    let object = [(0, 0, 0, 1), (0, 1, 0, 2), (1, 1, 0, 2), (1, 0, 0, 2)];
+    let mut r = result(&[&object, &object], 1.0 + 1.0 / 3.0);
+    r.prune();
+    for i in 0..2 {
+        assert_eq!(cubes(&r, i), 3);
+        assert!(r.grids[i].iter().all(|&id| id != 1));
+    }
+    assert!((r.score - 1.0 / 3.0).abs() < 1e-12);
+}
+
+#[test]
+fn pruned_answers_stay_valid() {
+    let mut improved = 0;
+    for seed in 1..=20 {
+        let tools_input = tools::gen(seed, Some(8));
+        let mut input = SolveInput::from_tools_input(&tools_input).unwrap();
+        input.budget = Budget::steps(60);
+        input.params.mc_run = 2;
+        let mut r = mc_solve(&mut Mcg128Xsl64::new(seed as u128), &input, input.d());
+        let before = r.score;
+        r.prune();
+        // 足す順番が違うので誤差は許す
+        assert!(r.score <= before + 1e-9);
+        if r.score < before - 1e-9 {
+            improved += 1;
+        }
+        let (score, err) = tools::compute_score(&tools_input, &r.to_output());
+        assert!(err.is_empty(), "seed={}: {}", seed, err);
+        assert_eq!(score, (r.score * 1e9).round() as i64, "seed={}", seed);
+    }
+    assert!(improved > 0);
+}
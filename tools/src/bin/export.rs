@@ -0,0 +1,61 @@
+use clap::Parser;
+use std::{fs::File, io::BufWriter, path::PathBuf};
+use tools::{export::*, *};
+
+#[derive(Parser, Debug)]
+struct Cli {
+    /// Path to input file
+    input: PathBuf,
+    /// Path to output file
+    output: PathBuf,
+    /// Path to the directory to write .vox, .obj and .stl files
+    #[clap(long = "dir", default_value = "export")]
+    dir: PathBuf,
+}
+
+fn create(path: PathBuf) -> BufWriter<File> {
+    BufWriter::new(File::create(&path).unwrap_or_else(|e| {
+        eprintln!("cannot create {}: {}", path.display(), e);
+        std::process::exit(1)
+    }))
+}
+
+fn read(path: &PathBuf) -> String {
+    std::fs::read_to_string(path).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", path.display());
+        std::process::exit(1)
+    })
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let input = parse_input(&read(&cli.input));
+    let out = parse_output(&input, &read(&cli.output)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1)
+    });
+    std::fs::create_dir_all(&cli.dir).unwrap();
+    write_mtl(&mut create(cli.dir.join("blocks.mtl")), &out).unwrap();
+    for i in 0..out.b.len() {
+        write_vox(
+            &mut create(cli.dir.join(format!("obj{}.vox", i + 1))),
+            &input,
+            &out,
+            i,
+        )
+        .unwrap();
+        write_obj(
+            &mut create(cli.dir.join(format!("obj{}.obj", i + 1))),
+            &out,
+            i,
+            "blocks.mtl",
+        )
+        .unwrap();
+        for id in 1..=out.n {
+            if out.b[i].iter().flatten().flatten().any(|&b| b == id) {
+                let path = cli.dir.join(format!("obj{}_block{}.stl", i + 1, id));
+                write_stl(&mut create(path), &out, i, id).unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use clap::Parser;
+use std::{
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+};
+use tools::*;
+
+#[derive(Parser, Debug)]
+struct Cli {
+    /// Path to input file, or input directory
+    input: PathBuf,
+    /// Path to output file, or output directory with files of the same names
+    output: PathBuf,
+}
+
+fn score(input: &Path, output: &Path) -> (i64, String) {
+    let input = match std::fs::read_to_string(input) {
+        Ok(s) => parse_input(&s),
+        Err(e) => return (0, format!("cannot read {}: {}", input.display(), e)),
+    };
+    let output = match std::fs::read_to_string(output) {
+        Ok(s) => s,
+        Err(e) => return (0, format!("cannot read {}: {}", output.display(), e)),
+    };
+    match parse_output(&input, &output) {
+        Ok(out) => compute_score(&input, &out),
+        Err(err) => (0, err),
+    }
+}
+
+/// `score` that reports a panic (e.g. a malformed input file) as an error.
+fn score_or_panic(input: &Path, output: &Path) -> (i64, String) {
+    panic::catch_unwind(AssertUnwindSafe(|| score(input, output))).unwrap_or_else(|e| {
+        let message = match e.downcast_ref::<String>() {
+            Some(s) => s.clone(),
+            None => e.downcast_ref::<&str>().unwrap_or(&"?").to_string(),
+        };
+        (0, format!("panicked: {}", message))
+    })
+}
+
+fn main() {
+    let cli = Cli::parse();
+    // パニックのメッセージはエラーの行に出す
+    panic::set_hook(Box::new(|_| {}));
+    let pairs: Vec<(PathBuf, PathBuf)> = if cli.input.is_dir() {
+        // README や .gitkeep などは入力ではない
+        let mut names: Vec<_> = std::fs::read_dir(&cli.input)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "txt"))
+            .map(|p| p.file_name().unwrap().to_owned())
+            .collect();
+        names.sort();
+        names
+            .into_iter()
+            .map(|name| (cli.input.join(&name), cli.output.join(&name)))
+            .collect()
+    } else {
+        vec![(cli.input.clone(), cli.output.clone())]
+    };
+
+    // common.py の summary_score と同じく、解の値 (score / 1e9) の平均と対数平均
+    let mut total = 0;
+    let mut log_sum = 0.0;
+    let mut valid = 0;
+    for (input, output) in &pairs {
+        let name = input.file_name().unwrap().to_string_lossy();
+        let (score, err) = score_or_panic(input, output);
+        if err.is_empty() {
+            println!("{} {}", name, score);
+            total += score;
+            log_sum += (score as f64 / 1e9).ln();
+            valid += 1;
+        } else {
+            println!("{} {} {}", name, score, err);
+        }
+    }
+    println!("total = {}", total);
+    if valid > 0 {
+        println!("mean = {}", total as f64 / 1e9 / valid as f64);
+        println!("log mean = {}", log_sum / valid as f64);
+    }
+    if valid < pairs.len() {
+        println!("invalid = {}", pairs.len() - valid);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,42 @@
+use clap::Parser;
+use std::path::PathBuf;
+use tools::*;
+
+#[derive(Parser, Debug)]
+struct Cli {
+    /// Path to input file
+    input: PathBuf,
+    /// Path to output file
+    output: PathBuf,
+    /// Path to the SVG to write
+    #[clap(long = "svg", default_value = "out.svg")]
+    svg: PathBuf,
+    /// Path to the HTML page to write
+    #[clap(long = "html", default_value = "vis.html")]
+    html: PathBuf,
+}
+
+fn read(path: &PathBuf) -> String {
+    std::fs::read_to_string(path).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", path.display());
+        std::process::exit(1)
+    })
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let input = parse_input(&read(&cli.input));
+    let out = parse_output(&input, &read(&cli.output)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1)
+    });
+    let (score, err) = compute_score(&input, &out);
+    let line = if err.is_empty() {
+        format!("Score = {}", score)
+    } else {
+        format!("Score = {} ({})", score, err)
+    };
+    println!("{}", line);
+    std::fs::write(&cli.svg, vis::vis(&input, &out)).unwrap();
+    std::fs::write(&cli.html, vis::html(&input, &out, &line)).unwrap();
+}
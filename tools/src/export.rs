@@ -0,0 +1,224 @@
+//! Writes solutions as MagicaVoxel `.vox` files and OBJ/STL meshes.
+
+use crate::{Input, Output};
+use std::io::{self, Write};
+
+/// Colour of unshared cubes.
+pub const NEUTRAL: [u8; 3] = [170, 170, 170];
+
+/// `shared[id]` is true when block `id` appears in two or more objects. `shared[0]` is unused.
+pub fn shared_blocks(out: &Output) -> Vec<bool> {
+    let mut used = vec![vec![false; out.b.len()]; out.n + 1];
+    for (i, b) in out.b.iter().enumerate() {
+        for &id in b.iter().flatten().flatten() {
+            used[id][i] = true;
+        }
+    }
+    used.iter()
+        .map(|u| u.iter().filter(|&&u| u).count() >= 2)
+        .collect()
+}
+
+/// Colour of block `id`: a distinct hue if shared, `NEUTRAL` otherwise.
+pub fn block_color(id: usize, shared: bool) -> [u8; 3] {
+    if !shared {
+        return NEUTRAL;
+    }
+    // 黄金比で色相をずらすと隣の id でも見分けやすい
+    let h = (id as f64 * 0.618_033_988_749_895).fract() * 6.0;
+    let (s, v) = (0.65, 0.95);
+    let c = v * s;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as usize {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = v - c;
+    [r, g, b].map(|t| ((t + m) * 255.0).round() as u8)
+}
+
+fn write_chunk<W: Write>(
+    w: &mut W,
+    id: &[u8; 4],
+    content: &[u8],
+    children: &[u8],
+) -> io::Result<()> {
+    w.write_all(id)?;
+    w.write_all(&(content.len() as u32).to_le_bytes())?;
+    w.write_all(&(children.len() as u32).to_le_bytes())?;
+    w.write_all(content)?;
+    w.write_all(children)
+}
+
+/// Writes object `i` (`0..out.b.len()`) as a MagicaVoxel `.vox` file.
+///
+/// Shared blocks take palette entries 1..=254 in id order and unshared cubes use entry 255.
+/// Every object gets the same palette, so a shared block has the same colour in each file.
+/// With more than 254 shared blocks the entries wrap around.
+pub fn write_vox<W: Write>(w: &mut W, input: &Input, out: &Output, i: usize) -> io::Result<()> {
+    let d = input.d;
+    let shared = shared_blocks(out);
+    let mut index = vec![255u8; out.n + 1];
+    let mut palette = vec![[0u8; 4]; 256];
+    palette[254] = [NEUTRAL[0], NEUTRAL[1], NEUTRAL[2], 255];
+    let mut k = 0;
+    for id in 1..=out.n {
+        if shared[id] {
+            index[id] = (k % 254 + 1) as u8;
+            if k < 254 {
+                let [r, g, b] = block_color(id, true);
+                palette[k] = [r, g, b, 255];
+            }
+            k += 1;
+        }
+    }
+
+    let mut size = vec![];
+    for _ in 0..3 {
+        size.extend_from_slice(&(d as u32).to_le_bytes());
+    }
+    let mut xyzi = vec![0; 4];
+    let mut count = 0u32;
+    for x in 0..d {
+        for y in 0..d {
+            for z in 0..d {
+                let id = out.b[i][x][y][z];
+                if id != 0 {
+                    xyzi.extend_from_slice(&[x as u8, y as u8, z as u8, index[id]]);
+                    count += 1;
+                }
+            }
+        }
+    }
+    xyzi[..4].copy_from_slice(&count.to_le_bytes());
+    let rgba: Vec<u8> = palette.concat();
+
+    let mut children = vec![];
+    write_chunk(&mut children, b"SIZE", &size, &[])?;
+    write_chunk(&mut children, b"XYZI", &xyzi, &[])?;
+    write_chunk(&mut children, b"RGBA", &rgba, &[])?;
+    w.write_all(b"VOX ")?;
+    w.write_all(&150u32.to_le_bytes())?;
+    write_chunk(w, b"MAIN", &[], &children)
+}
+
+/// One outward face of a block: its outward normal and four corners, counter-clockwise when
+/// seen from outside.
+#[derive(Clone, Debug)]
+pub struct Face {
+    pub normal: [i32; 3],
+    pub corners: [[usize; 3]; 4],
+}
+
+/// Faces of block `id` in object `i` that do not touch another cube of the same block.
+pub fn block_faces(out: &Output, i: usize, id: usize) -> Vec<Face> {
+    let b = &out.b[i];
+    let d = b.len();
+    let mut faces = vec![];
+    for x in 0..d {
+        for y in 0..d {
+            for z in 0..d {
+                if b[x][y][z] != id {
+                    continue;
+                }
+                let p = [x, y, z];
+                for axis in 0..3 {
+                    for positive in [false, true] {
+                        let mut q = p;
+                        q[axis] = if positive {
+                            q[axis] + 1
+                        } else {
+                            q[axis].wrapping_sub(1)
+                        };
+                        if q.iter().all(|&c| c < d) && b[q[0]][q[1]][q[2]] == id {
+                            continue;
+                        }
+                        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+                        let mut uv = [(0, 0), (1, 0), (1, 1), (0, 1)];
+                        if !positive {
+                            uv.reverse();
+                        }
+                        let mut corners = [p; 4];
+                        for (c, &(du, dv)) in corners.iter_mut().zip(&uv) {
+                            c[axis] += positive as usize;
+                            c[u] += du;
+                            c[v] += dv;
+                        }
+                        let mut normal = [0; 3];
+                        normal[axis] = if positive { 1 } else { -1 };
+                        faces.push(Face { normal, corners });
+                    }
+                }
+            }
+        }
+    }
+    faces
+}
+
+/// Writes a material `block{id}` for every block, to go with `write_obj`.
+pub fn write_mtl<W: Write>(w: &mut W, out: &Output) -> io::Result<()> {
+    for (id, &shared) in shared_blocks(out).iter().enumerate().skip(1) {
+        let [r, g, b] = block_color(id, shared);
+        writeln!(w, "newmtl block{}", id)?;
+        writeln!(
+            w,
+            "Kd {:.4} {:.4} {:.4}",
+            r as f64 / 255.0,
+            g as f64 / 255.0,
+            b as f64 / 255.0
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes object `i` as an OBJ mesh with one `o block{id}` per block.
+pub fn write_obj<W: Write>(w: &mut W, out: &Output, i: usize, mtl: &str) -> io::Result<()> {
+    writeln!(w, "mtllib {}", mtl)?;
+    let mut vertices = 0;
+    for id in 1..=out.n {
+        let faces = block_faces(out, i, id);
+        if faces.is_empty() {
+            continue;
+        }
+        writeln!(w, "o block{}", id)?;
+        writeln!(w, "usemtl block{}", id)?;
+        for face in &faces {
+            for [x, y, z] in face.corners {
+                writeln!(w, "v {} {} {}", x, y, z)?;
+            }
+            writeln!(
+                w,
+                "f {} {} {} {}",
+                vertices + 1,
+                vertices + 2,
+                vertices + 3,
+                vertices + 4
+            )?;
+            vertices += 4;
+        }
+    }
+    Ok(())
+}
+
+/// Writes block `id` of object `i` as an ASCII STL mesh.
+pub fn write_stl<W: Write>(w: &mut W, out: &Output, i: usize, id: usize) -> io::Result<()> {
+    writeln!(w, "solid block{}", id)?;
+    for face in block_faces(out, i, id) {
+        let [nx, ny, nz] = face.normal;
+        let c = face.corners;
+        for tri in [[c[0], c[1], c[2]], [c[0], c[2], c[3]]] {
+            writeln!(w, "facet normal {} {} {}", nx, ny, nz)?;
+            writeln!(w, "outer loop")?;
+            for [x, y, z] in tri {
+                writeln!(w, "vertex {} {} {}", x, y, z)?;
+            }
+            writeln!(w, "endloop")?;
+            writeln!(w, "endfacet")?;
+        }
+    }
+    writeln!(w, "endsolid block{}", id)
+}
@@ -1,6 +1,12 @@
-use proconio::{input, marker::Bytes};
+use proconio::{input, marker::Bytes, source::Source};
 use rand::prelude::*;
 
+pub mod export;
+mod rotation;
+pub mod vis;
+
+pub use rotation::Rotation;
+
 pub trait SetMinMax {
     fn setmin(&mut self, v: Self) -> bool;
     fn setmax(&mut self, v: Self) -> bool;
@@ -47,7 +53,7 @@ pub struct Input {
 impl std::fmt::Display for Input {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "{}", self.d)?;
-        for i in 0..2 {
+        for i in 0..self.f.len() {
             for z in 0..self.d {
                 for x in 0..self.d {
                     write!(f, "{}", self.f[i][z][x])?;
@@ -71,20 +77,25 @@ pub fn parse_input(f: &str) -> Input {
         from &mut f,
         d: usize,
     }
-    let mut fs = mat![0; 2; d; d];
-    let mut rs = mat![0; 2; d; d];
-    for i in 0..2 {
+    let mut fs = vec![];
+    let mut rs = vec![];
+    // 3 個目以降の物体も続いていれば読む
+    while fs.len() < 2 || !f.is_empty() {
         input! {
             from &mut f,
             f: [Bytes; d],
             r: [Bytes; d],
         }
+        let mut fi = mat![0; d; d];
+        let mut ri = mat![0; d; d];
         for z in 0..d {
             for x in 0..d {
-                fs[i][z][x] = (f[z][x] - b'0') as i32;
-                rs[i][z][x] = (r[z][x] - b'0') as i32;
+                fi[z][x] = (f[z][x] - b'0') as i32;
+                ri[z][x] = (r[z][x] - b'0') as i32;
             }
         }
+        fs.push(fi);
+        rs.push(ri);
     }
     Input { d, f: fs, r: rs }
 }
@@ -110,10 +121,10 @@ fn read<T: Copy + PartialOrd + std::fmt::Display + std::str::FromStr>(
 }
 
 pub fn parse_output(input: &Input, f: &str) -> Result<Output, String> {
-    let mut b = mat![0; 2; input.d; input.d; input.d];
+    let mut b = mat![0; input.f.len(); input.d; input.d; input.d];
     let mut tokens = f.split_whitespace();
     let n = read(tokens.next(), 0, 1000000)?;
-    for i in 0..2 {
+    for i in 0..input.f.len() {
         for x in 0..input.d {
             for y in 0..input.d {
                 for z in 0..input.d {
@@ -142,54 +153,37 @@ fn normalize(b: &[(usize, usize, usize)]) -> Vec<(usize, usize, usize)> {
         .collect()
 }
 
-fn is_same(b1: &[(usize, usize, usize)], b2: &[(usize, usize, usize)]) -> bool {
+/// Whether `b2` is `b1` rotated and translated.
+pub fn is_same(b1: &[(usize, usize, usize)], b2: &[(usize, usize, usize)]) -> bool {
     if b1.len() != b2.len() {
         return false;
     }
-    let b1 = {
-        let mut b1 = normalize(b1);
-        b1.sort();
-        b1
-    };
-    let mut b2 = normalize(b2);
-    let mut max_x = 0;
-    let mut max_y = 0;
-    let mut max_z = 0;
-    for &(x, y, z) in &b2 {
-        max_x.setmax(x);
-        max_y.setmax(y);
-        max_z.setmax(z);
-    }
-    for i in 0..6 {
-        for _ in 0..4 {
-            b2.sort();
-            if b1 == b2 {
-                return true;
+    let mut b1 = normalize(b1);
+    b1.sort();
+    Rotation::all().any(|rot| {
+        let rotated: Vec<_> = b2
+            .iter()
+            .map(|&(x, y, z)| rot.apply([x as i32, y as i32, z as i32]))
+            .collect();
+        let mut min = [i32::MAX; 3];
+        for v in rotated.iter() {
+            for k in 0..3 {
+                min[k].setmin(v[k]);
             }
-            for (x, y, _) in &mut b2 {
-                let t = *x;
-                *x = max_y - *y;
-                *y = t;
-            }
-            std::mem::swap(&mut max_x, &mut max_y);
         }
-        if i & 1 != 0 {
-            for (_, y, z) in &mut b2 {
-                let t = *y;
-                *y = max_z - *z;
-                *z = t;
-            }
-            std::mem::swap(&mut max_y, &mut max_z);
-        } else {
-            for (x, _, z) in &mut b2 {
-                let t = *z;
-                *z = max_x - *x;
-                *x = t;
-            }
-            std::mem::swap(&mut max_x, &mut max_z);
-        }
-    }
-    false
+        let mut b2: Vec<_> = rotated
+            .iter()
+            .map(|v| {
+                (
+                    (v[0] - min[0]) as usize,
+                    (v[1] - min[1]) as usize,
+                    (v[2] - min[2]) as usize,
+                )
+            })
+            .collect();
+        b2.sort();
+        b1 == b2
+    })
 }
 
 pub const D2: [(usize, usize); 4] = [(0, !0), (0, 1), (!0, 0), (1, 0)];
@@ -203,9 +197,10 @@ pub const D3: [(usize, usize, usize); 6] = [
 ];
 
 pub fn compute_score(input: &Input, out: &Output) -> (i64, String) {
-    let mut pos = mat![vec![]; 2; out.n];
-    let mut visited = mat![false; 2; input.d; input.d; input.d];
-    for i in 0..2 {
+    let objects = input.f.len();
+    let mut pos = mat![vec![]; objects; out.n];
+    let mut visited = mat![false; objects; input.d; input.d; input.d];
+    for i in 0..objects {
         let mut f = mat![0; input.d; input.d];
         let mut r = mat![0; input.d; input.d];
         for x in 0..input.d {
@@ -260,20 +255,26 @@ pub fn compute_score(input: &Input, out: &Output) -> (i64, String) {
     }
     let mut sum = 0.0f64;
     for i in 0..out.n {
-        if pos[0][i].len() == 0 && pos[1][i].len() == 0 {
+        let used: Vec<usize> = (0..objects).filter(|&j| pos[j][i].len() > 0).collect();
+        if used.is_empty() {
             return (0, format!("block {} is not used", i + 1));
-        } else if pos[0][i].len() == 0 || pos[1][i].len() == 0 {
-            sum += (pos[0][i].len() + pos[1][i].len()) as f64;
-        } else if is_same(&pos[0][i], &pos[1][i]) {
-            sum += 1.0 / pos[0][i].len() as f64;
-        } else {
+        } else if used.len() == 1 {
+            sum += pos[used[0]][i].len() as f64;
+        } else if let Some(&j) = used[1..]
+            .iter()
+            .find(|&&j| !is_same(&pos[used[0]][i], &pos[j][i]))
+        {
             return (
                 0,
                 format!(
-                    "The shape of block {} differs between objects 1 and 2.",
-                    i + 1
+                    "The shape of block {} differs between objects {} and {}.",
+                    i + 1,
+                    used[0] + 1,
+                    j + 1
                 ),
             );
+        } else {
+            sum += 1.0 / pos[used[0]][i].len() as f64;
         }
     }
     let score = (1e9 * sum).round() as i64;
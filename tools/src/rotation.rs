@@ -0,0 +1,103 @@
+/// A proper rotation of the cube (one of the 24 elements of its rotation group).
+///
+/// Stored as the image of each direction, with the directions numbered
+/// 0: +x, 1: -x, 2: +y, 3: -y, 4: +z, 5: -z.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Rotation([u8; 6]);
+
+/// `+a × +b` for the axes `a`, `b`.
+fn cross_dir(a: u8, b: u8) -> Option<u8> {
+    let (axis_a, axis_b) = (a / 2, b / 2);
+    if axis_a == axis_b {
+        return None;
+    }
+    let axis_c = 3 - axis_a - axis_b;
+    let cyclic = (axis_a + 1) % 3 == axis_b;
+    let negative = (a & 1 == 1) ^ (b & 1 == 1) ^ !cyclic;
+    Some(axis_c * 2 + negative as u8)
+}
+
+impl Rotation {
+    pub const IDENTITY: Rotation = Rotation([0, 1, 2, 3, 4, 5]);
+
+    /// The rotation sending +x to `x` and +y to `y`, if the two are perpendicular.
+    pub fn from_axes(x: u8, y: u8) -> Option<Rotation> {
+        let z = cross_dir(x, y)?;
+        Some(Rotation([x, x ^ 1, y, y ^ 1, z, z ^ 1]))
+    }
+
+    /// The unique rotation sending `from1` to `to1` and `from2` to `to2`.
+    pub fn from_pairs(from1: u8, to1: u8, from2: u8, to2: u8) -> Option<Rotation> {
+        let from = Rotation::from_axes(from1, from2)?;
+        let to = Rotation::from_axes(to1, to2)?;
+        Some(to.compose(from.inverse()))
+    }
+
+    /// A half turn sending `from` to `to` (the identity if they are equal).
+    ///
+    /// For perpendicular directions the turn is around their bisector, for opposite ones
+    /// around the y axis (x) or the x axis (y, z).
+    pub fn half_turn(from: u8, to: u8) -> Rotation {
+        let mut map = [0, 1, 2, 3, 4, 5];
+        if from == to {
+            return Rotation(map);
+        }
+        if from == to ^ 1 {
+            let pivot = if from / 2 == 0 { 1 } else { 0 };
+            for (d, m) in map.iter_mut().enumerate() {
+                if d as u8 / 2 != pivot {
+                    *m ^= 1;
+                }
+            }
+            return Rotation(map);
+        }
+        for (d, m) in map.iter_mut().enumerate() {
+            let d = d as u8;
+            *m = if d == from || d == from ^ 1 {
+                to ^ d ^ from
+            } else if d == to || d == to ^ 1 {
+                from ^ d ^ to
+            } else {
+                d ^ 1
+            };
+        }
+        Rotation(map)
+    }
+
+    /// All 24 rotations.
+    pub fn all() -> impl Iterator<Item = Rotation> {
+        (0..6).flat_map(|x| (0..6).filter_map(move |y| Rotation::from_axes(x, y)))
+    }
+
+    #[inline]
+    pub fn apply_dir(self, direction: u8) -> u8 {
+        self.0[direction as usize]
+    }
+
+    /// Rotates the vector `v` around the origin.
+    pub fn apply(self, v: [i32; 3]) -> [i32; 3] {
+        let mut w = [0; 3];
+        for (axis, &c) in v.iter().enumerate() {
+            let dir = self.0[axis * 2];
+            w[dir as usize / 2] = if dir & 1 == 0 { c } else { -c };
+        }
+        w
+    }
+
+    /// `self` after `other`.
+    pub fn compose(self, other: Rotation) -> Rotation {
+        let mut map = [0; 6];
+        for (m, &o) in map.iter_mut().zip(other.0.iter()) {
+            *m = self.0[o as usize];
+        }
+        Rotation(map)
+    }
+
+    pub fn inverse(self) -> Rotation {
+        let mut map = [0; 6];
+        for (d, &m) in self.0.iter().enumerate() {
+            map[m as usize] = d as u8;
+        }
+        Rotation(map)
+    }
+}
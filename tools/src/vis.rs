@@ -0,0 +1,283 @@
+//! SVG and HTML rendering of a solution.
+
+use crate::{
+    export::{block_color, shared_blocks},
+    Input, Output,
+};
+use std::fmt::Write;
+
+/// Size of one cube or pixel in px.
+const CELL: f64 = 16.0;
+const COS30: f64 = 0.866_025_403_784_438_6;
+
+const MISSING: &str = "#e53935";
+const EXTRA: &str = "#fb8c00";
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn shade(c: [u8; 3], k: f64) -> [u8; 3] {
+    c.map(|t| (t as f64 * k).round().min(255.0) as u8)
+}
+
+fn colors(out: &Output) -> Vec<[u8; 3]> {
+    shared_blocks(out)
+        .iter()
+        .enumerate()
+        .map(|(id, &shared)| block_color(id, shared))
+        .collect()
+}
+
+/// Width and height of `iso`.
+pub fn iso_size(d: usize) -> (f64, f64) {
+    (2.0 * d as f64 * COS30 * CELL, 2.0 * d as f64 * CELL)
+}
+
+/// Object `i` seen from `(+x, +y, +z)` as an SVG group, with `z` pointing up.
+pub fn iso(out: &Output, i: usize) -> String {
+    let b = &out.b[i];
+    let d = b.len();
+    let colors = colors(out);
+    let mut cubes = vec![];
+    for (x, bx) in b.iter().enumerate() {
+        for (y, bxy) in bx.iter().enumerate() {
+            for (z, &id) in bxy.iter().enumerate() {
+                if id != 0 {
+                    cubes.push((x, y, z));
+                }
+            }
+        }
+    }
+    // 手前 (x + y + z が大きい) ほど後に描く
+    cubes.sort_by_key(|&(x, y, z)| x + y + z);
+    let project = |x: usize, y: usize, z: usize| {
+        let sx = (x as f64 - y as f64) * COS30 * CELL + d as f64 * COS30 * CELL;
+        let sy = (x + y) as f64 * 0.5 * CELL - z as f64 * CELL + d as f64 * CELL;
+        (sx, sy)
+    };
+    let mut svg = String::new();
+    svg += "<g stroke=\"#333\" stroke-width=\"0.5\">";
+    for (x, y, z) in cubes {
+        let c = colors[b[x][y][z]];
+        let faces = [
+            (
+                [
+                    (x, y, z + 1),
+                    (x + 1, y, z + 1),
+                    (x + 1, y + 1, z + 1),
+                    (x, y + 1, z + 1),
+                ],
+                1.0,
+            ),
+            (
+                [
+                    (x + 1, y, z),
+                    (x + 1, y + 1, z),
+                    (x + 1, y + 1, z + 1),
+                    (x + 1, y, z + 1),
+                ],
+                0.8,
+            ),
+            (
+                [
+                    (x, y + 1, z),
+                    (x + 1, y + 1, z),
+                    (x + 1, y + 1, z + 1),
+                    (x, y + 1, z + 1),
+                ],
+                0.6,
+            ),
+        ];
+        for (corners, k) in faces {
+            let points: Vec<_> = corners
+                .iter()
+                .map(|&(x, y, z)| {
+                    let (sx, sy) = project(x, y, z);
+                    format!("{:.1},{:.1}", sx, sy)
+                })
+                .collect();
+            write!(
+                svg,
+                "<polygon points=\"{}\" fill=\"{}\"><title>block {}</title></polygon>",
+                points.join(" "),
+                hex(shade(c, k)),
+                b[x][y][z]
+            )
+            .unwrap();
+        }
+    }
+    svg += "</g>";
+    svg
+}
+
+/// Front (`right == false`) or right silhouette of object `i` as an SVG group, `z` up.
+///
+/// Pixels the silhouette needs but the output leaves empty are red, and pixels the output
+/// covers but the silhouette does not allow are orange.
+#[allow(clippy::needless_range_loop)]
+pub fn silhouette(input: &Input, out: &Output, i: usize, right: bool) -> String {
+    let d = input.d;
+    let b = &out.b[i];
+    let mut svg = String::new();
+    svg += "<g stroke=\"#ccc\" stroke-width=\"0.5\">";
+    for z in 0..d {
+        for h in 0..d {
+            let want = if right {
+                input.r[i][z][h]
+            } else {
+                input.f[i][z][h]
+            } == 1;
+            let have = (0..d).any(|k| if right { b[k][h][z] } else { b[h][k][z] } != 0);
+            let fill = match (want, have) {
+                (true, true) => "#555",
+                (true, false) => MISSING,
+                (false, true) => EXTRA,
+                (false, false) => "#fff",
+            };
+            write!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                h as f64 * CELL,
+                (d - 1 - z) as f64 * CELL,
+                CELL,
+                CELL,
+                fill
+            )
+            .unwrap();
+        }
+    }
+    svg += "</g>";
+    svg
+}
+
+/// Layer `z` of object `i` seen from above, `x` to the right and `y` down.
+#[allow(clippy::needless_range_loop)]
+pub fn slice(out: &Output, i: usize, z: usize) -> String {
+    let b = &out.b[i];
+    let d = b.len();
+    let colors = colors(out);
+    let mut svg = String::new();
+    svg += "<g stroke=\"#ccc\" stroke-width=\"0.5\" font-size=\"8\" text-anchor=\"middle\">";
+    for x in 0..d {
+        for y in 0..d {
+            let id = b[x][y][z];
+            let fill = if id == 0 {
+                "#fff".to_owned()
+            } else {
+                hex(colors[id])
+            };
+            let (px, py) = (x as f64 * CELL, y as f64 * CELL);
+            write!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                px, py, CELL, CELL, fill
+            )
+            .unwrap();
+            if id != 0 {
+                write!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\" stroke=\"none\">{}</text>",
+                    px + CELL / 2.0,
+                    py + CELL * 0.7,
+                    id
+                )
+                .unwrap();
+            }
+        }
+    }
+    svg += "</g>";
+    svg
+}
+
+/// Every object in isometric view with their front and right silhouettes below.
+pub fn vis(input: &Input, out: &Output) -> String {
+    let d = input.d as f64;
+    let (w, h) = iso_size(input.d);
+    // シルエット 2 枚の方が広い
+    let w = w.max((2.0 * d + 1.0) * CELL);
+    let margin = CELL;
+    let n = out.b.len() as f64;
+    let width = n * w + (n + 1.0) * margin;
+    let height = h + d * CELL + 3.0 * margin;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+        width, height, width, height
+    );
+    for i in 0..out.b.len() {
+        let left = margin + i as f64 * (w + margin);
+        write!(
+            svg,
+            "<g transform=\"translate({},{})\">{}</g>",
+            left,
+            margin,
+            iso(out, i)
+        )
+        .unwrap();
+        for (k, &right) in [false, true].iter().enumerate() {
+            write!(
+                svg,
+                "<g transform=\"translate({},{})\">{}</g>",
+                left + k as f64 * (d + 1.0) * CELL,
+                h + 2.0 * margin,
+                silhouette(input, out, i, right)
+            )
+            .unwrap();
+        }
+    }
+    svg += "</svg>";
+    svg
+}
+
+/// A self-contained HTML page with `vis`, the score line and a slider over `z` slices.
+pub fn html(input: &Input, out: &Output, score: &str) -> String {
+    let d = input.d;
+    let size = d as f64 * CELL;
+    let mut slices = String::new();
+    for z in 0..d {
+        write!(
+            slices,
+            "<g class=\"slice\" data-z=\"{}\"{}>",
+            z,
+            if z == 0 { "" } else { " display=\"none\"" }
+        )
+        .unwrap();
+        for i in 0..out.b.len() {
+            write!(
+                slices,
+                "<g transform=\"translate({},0)\">{}</g>",
+                i as f64 * (size + CELL),
+                slice(out, i, z)
+            )
+            .unwrap();
+        }
+        slices += "</g>";
+    }
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>vis</title></head>
+<body>
+<p>{score}</p>
+{vis}
+<p><label>z = <span id="z">0</span> <input id="slider" type="range" min="0" max="{max}" value="0"></label></p>
+<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{size}">{slices}</svg>
+<script>
+document.getElementById("slider").addEventListener("input", (e) => {{
+  document.getElementById("z").textContent = e.target.value;
+  for (const g of document.querySelectorAll(".slice")) {{
+    g.setAttribute("display", g.dataset.z === e.target.value ? "inline" : "none");
+  }}
+}});
+</script>
+</body>
+</html>
+"#,
+        score = score,
+        vis = vis(input, out),
+        max = d - 1,
+        width = out.b.len() as f64 * (size + CELL) - CELL,
+        size = size,
+        slices = slices
+    )
+}